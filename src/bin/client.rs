@@ -1,10 +1,75 @@
+use clap::Parser;
 use wizard::client::client::Client;
+use wizard::client::config::{ClientArgs, ClientConfig, ClientKind};
+use wizard::client::heuristic_client::HeuristicClient;
+use wizard::client::human_client::HumanClient;
+use wizard::client::ismcts_client::IsmctsClient;
+use wizard::client::process_client::ProcessClient;
 use wizard::client::random_client::RandomClient;
+use wizard::client::spectator_client::spectate;
+use wizard::game::event::write_event_log;
 
 fn main() {
-    let mut client = RandomClient::new();
+    let config = match ClientConfig::from_args(ClientArgs::parse()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error occurred: {e:#}");
+            std::process::exit(1);
+        }
+    };
 
-    if let Err(e) = client.client("0.0.0.0", "7878") {
-        eprintln!("Error occurred: {e}");
+    let port = config.port.to_string();
+    let table = config.table_action();
+    let result: anyhow::Result<()> = match config.client {
+        ClientKind::Random => {
+            let mut client = match config.seed {
+                Some(seed) => RandomClient::with_seed(seed),
+                None => RandomClient::new(),
+            };
+            client
+                .lobby_client(&config.host, &port, &config.name, table)
+                .map_err(Into::into)
+        }
+        ClientKind::Heuristic => HeuristicClient::new()
+            .lobby_client(&config.host, &port, &config.name, table)
+            .map_err(Into::into),
+        ClientKind::Ismcts => {
+            let mut client = match config.seed {
+                Some(seed) => IsmctsClient::with_seed(seed),
+                None => IsmctsClient::new(),
+            };
+            client.set_budget(config.search_budget());
+            client
+                .lobby_client(&config.host, &port, &config.name, table)
+                .map_err(Into::into)
+        }
+        ClientKind::External => match &config.bot {
+            Some(program) => ProcessClient::spawn(program, &config.bot_args, config.bot_timeout())
+                .and_then(|mut client| {
+                    client
+                        .lobby_client(&config.host, &port, &config.name, table)
+                        .map_err(Into::into)
+                }),
+            None => Err(anyhow::anyhow!("An external bot needs --bot")),
+        },
+        ClientKind::Human => HumanClient::new()
+            .lobby_client(&config.host, &port, &config.name, table)
+            .map_err(Into::into),
+        ClientKind::Spectator => spectate(
+            &config.host,
+            &port,
+            &config.name,
+            config.join_table,
+            config.admin_token.clone(),
+        )
+        .and_then(|events| match &config.record {
+            Some(path) => write_event_log(path, &events),
+            None => Ok(()),
+        }),
+    };
+
+    if let Err(e) = result {
+        eprintln!("Error occurred: {e:#}");
+        std::process::exit(1);
     }
 }
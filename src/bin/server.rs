@@ -1,6 +1,24 @@
+use clap::Parser;
+use wizard::server::config::{ServerArgs, ServerConfig};
+use wizard::server::lobby::Lobby;
 use wizard::server::server::Server;
 
 fn main() {
-    let mut server = Server {};
-    server.start_server(3);
+    let config = match ServerConfig::from_args(ServerArgs::parse()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error occurred: {e:#}");
+            std::process::exit(1);
+        }
+    };
+
+    let result = if config.lobby {
+        Lobby::new(config).run()
+    } else {
+        Server::new(config).start_server()
+    };
+    if let Err(e) = result {
+        eprintln!("Error occurred: {e:#}");
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,71 @@
+use anyhow::{Context, Result};
+use clap::Parser;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use wizard::client::ismcts_client::SearchBudget;
+use wizard::simulation::simulator::{BotKind, Simulation};
+
+/// Command line arguments for the simulator binary
+#[derive(Clone, Debug, Parser)]
+#[command(about = "Play many games of Wizard between bots and compare them", long_about = None)]
+struct SimulateArgs {
+    /// Bot in each seat of the first game, e.g. `heuristic,random,random`
+    #[arg(long, value_enum, value_delimiter = ',', required = true)]
+    bots: Vec<BotKind>,
+    /// Number of games to play, or of deals to play from every seat with `--duplicate`
+    #[arg(short, long, default_value_t = 1000)]
+    games: usize,
+    /// Play every deal once with the lineup in each seat and compare scores on the same deals
+    #[arg(long)]
+    duplicate: bool,
+    /// Seed of the first game's deal
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// Games played at the same time. Every core by default.
+    #[arg(long)]
+    threads: Option<usize>,
+    /// TOML file with the game rules, e.g. `restricted_bidding = true`
+    #[arg(long)]
+    rules: Option<PathBuf>,
+    /// Deals the search bot plays out before each decision
+    #[arg(long, conflicts_with = "think_ms")]
+    iterations: Option<u32>,
+    /// Milliseconds the search bot thinks before each decision
+    #[arg(long)]
+    think_ms: Option<u64>,
+}
+
+fn simulate(args: SimulateArgs) -> Result<()> {
+    let mut simulation = Simulation::new(args.bots, args.games);
+    simulation.seed = args.seed;
+    simulation.duplicate = args.duplicate;
+    if let Some(threads) = args.threads {
+        simulation.threads = threads;
+    }
+    if let Some(path) = &args.rules {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read rules file {}", path.display()))?;
+        simulation.rules = toml::from_str(&contents)
+            .with_context(|| format!("Invalid rules file {}", path.display()))?;
+    }
+    if let Some(iterations) = args.iterations {
+        simulation.search_budget = SearchBudget::Iterations(iterations);
+    }
+    if let Some(ms) = args.think_ms {
+        simulation.search_budget = SearchBudget::Time(Duration::from_millis(ms));
+    }
+
+    let started = Instant::now();
+    let report = simulation.run()?;
+    print!("{}", report);
+    println!("Finished in {:.1}s", started.elapsed().as_secs_f64());
+
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = simulate(SimulateArgs::parse()) {
+        eprintln!("Error occurred: {e:#}");
+        std::process::exit(1);
+    }
+}
@@ -12,16 +12,47 @@ pub enum Card {
     SpecialCard(SpecialCard),
 }
 
+impl Card {
+    /// Whether the card is a Juggler or Cloud that still needs a suit chosen to be played
+    pub fn needs_suit(&self) -> bool {
+        matches!(
+            self,
+            Card::SpecialCard(SpecialCard::Juggler(None) | SpecialCard::Cloud(None))
+        )
+    }
+
+    /// The card played as `suit` if it is a Juggler or Cloud that still needs a suit
+    pub fn with_chosen_suit(self, suit: Suit) -> Card {
+        match self {
+            Card::SpecialCard(SpecialCard::Juggler(None)) => {
+                Card::SpecialCard(SpecialCard::Juggler(Some(suit)))
+            }
+            Card::SpecialCard(SpecialCard::Cloud(None)) => {
+                Card::SpecialCard(SpecialCard::Cloud(Some(suit)))
+            }
+            card => card,
+        }
+    }
+
+    /// The card as it is held in a hand, without the suit chosen when playing it
+    pub fn without_chosen_suit(self) -> Card {
+        match self {
+            Card::SpecialCard(SpecialCard::Juggler(_)) => {
+                Card::SpecialCard(SpecialCard::Juggler(None))
+            }
+            Card::SpecialCard(SpecialCard::Cloud(_)) => Card::SpecialCard(SpecialCard::Cloud(None)),
+            card => card,
+        }
+    }
+}
+
 impl Serialize for Card {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
         let ser_str = match self {
-            Card::SpecialCard(special_card) => match special_card {
-                SpecialCard::Wizard => "Wizard".to_string(),
-                SpecialCard::Jester => "Jester".to_string(),
-            },
+            Card::SpecialCard(special_card) => special_card.to_string(),
             Card::NormalCard(normal_card) => {
                 format!("{}", normal_card)
             }
@@ -49,8 +80,6 @@ impl<'de> Deserialize<'de> for Card {
                 E: de::Error,
             {
                 match value {
-                    "Wizard" => Ok(Card::SpecialCard(SpecialCard::Wizard)),
-                    "Jester" => Ok(Card::SpecialCard(SpecialCard::Jester)),
                     "AC" => Ok(Card::NormalCard(NormalCard {
                         suit: Suit::Club,
                         rank: Rank::Ace,
@@ -263,7 +292,11 @@ impl<'de> Deserialize<'de> for Card {
                         rank: Rank::King,
                     })),
 
-                    _ => Err(de::Error::unknown_variant(value, &["Card"])),
+                    _ => value
+                        .parse::<SpecialCard>()
+                        .map(Card::SpecialCard)
+                        .or_else(|_| value.parse::<NormalCard>().map(Card::NormalCard))
+                        .map_err(|_| de::Error::unknown_variant(value, &["Card"])),
                 }
             }
         }
@@ -271,3 +304,63 @@ impl<'de> Deserialize<'de> for Card {
         deserializer.deserialize_str(CardVisitor)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn special_cards_to_and_from_json() {
+        let cloud = Card::SpecialCard(SpecialCard::Cloud(Some(Suit::Diamond)));
+        assert_eq!("\"CloudD\"", serde_json::to_string(&cloud).unwrap());
+        assert_eq!(cloud, serde_json::from_str("\"CloudD\"").unwrap());
+        assert_eq!(
+            Card::SpecialCard(SpecialCard::Bomb),
+            serde_json::from_str("\"Bomb\"").unwrap()
+        );
+        assert!(serde_json::from_str::<Card>("\"Bombs\"").is_err());
+    }
+
+    #[test]
+    fn extra_suits_and_ranks_to_and_from_json() {
+        let card = |suit, rank| Card::NormalCard(NormalCard { suit, rank });
+        for (name, card) in [
+            ("1M", card(Suit::Moon, Rank::One)),
+            ("15L", card(Suit::Leaf, Rank::Fifteen)),
+            ("10M", card(Suit::Moon, Rank::Ten)),
+            ("15H", card(Suit::Heart, Rank::Fifteen)),
+        ] {
+            assert_eq!(
+                format!("\"{}\"", name),
+                serde_json::to_string(&card).unwrap()
+            );
+            assert_eq!(
+                card,
+                serde_json::from_str(&format!("\"{}\"", name)).unwrap()
+            );
+        }
+        assert_eq!(
+            Card::SpecialCard(SpecialCard::Juggler(Some(Suit::Moon))),
+            serde_json::from_str("\"JugglerM\"").unwrap()
+        );
+        assert!(serde_json::from_str::<Card>("\"16H\"").is_err());
+        assert!(serde_json::from_str::<Card>("\"1X\"").is_err());
+    }
+
+    #[test]
+    fn chosen_suit() {
+        let juggler = Card::SpecialCard(SpecialCard::Juggler(None));
+        let wizard = Card::SpecialCard(SpecialCard::Wizard);
+
+        assert!(juggler.needs_suit());
+        assert!(!wizard.needs_suit());
+        let played = juggler.with_chosen_suit(Suit::Club);
+        assert_eq!(
+            Card::SpecialCard(SpecialCard::Juggler(Some(Suit::Club))),
+            played
+        );
+        assert!(!played.needs_suit());
+        assert_eq!(juggler, played.without_chosen_suit());
+        assert_eq!(wizard, wizard.with_chosen_suit(Suit::Club));
+    }
+}
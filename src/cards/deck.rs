@@ -5,10 +5,78 @@ use crate::cards::special_card::SpecialCard;
 use crate::cards::suit::Suit;
 use anyhow::{bail, Result};
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::{thread_rng, Rng};
 use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 
+/// Cards a deck is built from
+///
+/// Every suit has the standard ranks less `removed_ranks`, plus `extra_ranks`. Suits and ranks
+/// must not repeat.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DeckSpec {
+    pub wizards: u8,
+    pub jesters: u8,
+    /// Suits the normal cards are made of, which may include the extra suits `Moon` and `Leaf`
+    pub suits: Vec<Suit>,
+    /// Ranks left out of every suit
+    pub removed_ranks: Vec<Rank>,
+    /// Extra ranks added to every suit: `One` below Two, `Fifteen` above Ace
+    pub extra_ranks: Vec<Rank>,
+}
+
+impl Default for DeckSpec {
+    /// 52 normal cards plus four Wizards and four Jesters
+    fn default() -> Self {
+        DeckSpec {
+            wizards: 4,
+            jesters: 4,
+            suits: Suit::iter().collect(),
+            removed_ranks: Vec::new(),
+            extra_ranks: Vec::new(),
+        }
+    }
+}
+
+impl DeckSpec {
+    /// Standard deck suited to the number of players
+    ///
+    /// Two players leave out the 2 to 5 of every suit so a full game is 22 rounds rather than 30.
+    /// Seven or eight players add two Wizards and two Jesters so a full game is 9 or 8 rounds.
+    pub fn for_table(num_players: usize) -> DeckSpec {
+        match num_players {
+            0..=2 => DeckSpec {
+                removed_ranks: vec![Rank::Two, Rank::Three, Rank::Four, Rank::Five],
+                ..Default::default()
+            },
+            3..=6 => DeckSpec::default(),
+            _ => DeckSpec {
+                wizards: 6,
+                jesters: 6,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Ranks every suit of the deck has
+    pub fn ranks(&self) -> Vec<Rank> {
+        Rank::iter()
+            .filter(|rank| !self.removed_ranks.contains(rank))
+            .chain(self.extra_ranks.iter().copied())
+            .collect()
+    }
+
+    /// Number of cards in a deck built from this spec, not counting extra special cards
+    pub fn len(&self) -> usize {
+        self.suits.len() * self.ranks().len() + self.wizards as usize + self.jesters as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Deck {
     cards: Vec<Card>,
@@ -17,33 +85,66 @@ pub struct Deck {
 impl Deck {
     /// Create a new SHUFFLED deck
     pub fn new() -> Deck {
+        Deck::new_with_rng(&mut thread_rng())
+    }
+
+    /// Create a new deck SHUFFLED by `rng`
+    ///
+    /// The same rng state always produces the same deck
+    pub fn new_with_rng<R: Rng + ?Sized>(rng: &mut R) -> Deck {
+        Deck::with_special_cards(rng, &[])
+    }
+
+    /// Create a new deck with one of each of `special_cards` added, SHUFFLED by `rng`
+    pub fn with_special_cards<R: Rng + ?Sized>(rng: &mut R, special_cards: &[SpecialCard]) -> Deck {
+        Deck::from_spec(rng, &DeckSpec::default(), special_cards)
+    }
+
+    /// Create a new deck built from `spec` with one of each of `special_cards` added, SHUFFLED by
+    /// `rng`
+    pub fn from_spec<R: Rng + ?Sized>(
+        rng: &mut R,
+        spec: &DeckSpec,
+        special_cards: &[SpecialCard],
+    ) -> Deck {
         let mut cards = Vec::new();
 
-        for suit in Suit::iter() {
-            for rank in Rank::iter() {
+        let ranks = spec.ranks();
+        for suit in spec.suits.iter() {
+            for rank in ranks.iter() {
                 cards.push(Card::NormalCard(NormalCard {
-                    suit: suit.clone(),
-                    rank,
+                    suit: *suit,
+                    rank: *rank,
                 }));
             }
         }
 
-        for _ in 0..4 {
+        for _ in 0..spec.wizards {
             cards.push(Card::SpecialCard(SpecialCard::Wizard));
+        }
+        for _ in 0..spec.jesters {
             cards.push(Card::SpecialCard(SpecialCard::Jester));
         }
 
+        for special_card in special_cards.iter() {
+            cards.push(Card::SpecialCard(*special_card));
+        }
+
         let mut deck = Deck { cards };
 
-        deck.shuffle();
+        deck.shuffle_with_rng(rng);
 
         deck
     }
 
     /// Shuffle the remaining cards in the deck
     pub fn shuffle(&mut self) {
-        let mut rng = thread_rng();
-        self.cards.shuffle(&mut rng);
+        self.shuffle_with_rng(&mut thread_rng());
+    }
+
+    /// Shuffle the remaining cards in the deck using `rng`
+    pub fn shuffle_with_rng<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.cards.shuffle(rng);
     }
 
     /// Deal N cards
@@ -60,9 +161,17 @@ impl Deck {
     }
 }
 
+impl Default for Deck {
+    fn default() -> Self {
+        Deck::new()
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
     use std::collections::HashSet;
 
     #[test]
@@ -73,6 +182,16 @@ mod test {
         assert_ne!(deck, deck2);
     }
 
+    #[test]
+    fn seeded_deck() {
+        let deck = Deck::new_with_rng(&mut ChaCha8Rng::seed_from_u64(7));
+        let deck2 = Deck::new_with_rng(&mut ChaCha8Rng::seed_from_u64(7));
+        let deck3 = Deck::new_with_rng(&mut ChaCha8Rng::seed_from_u64(8));
+
+        assert_eq!(deck, deck2);
+        assert_ne!(deck, deck3);
+    }
+
     #[test]
     fn deck_60_cards() {
         for _ in 0..5 {
@@ -135,6 +254,67 @@ mod test {
         assert_eq!(52, normal_cards.len());
     }
 
+    #[test]
+    fn deck_with_special_cards() {
+        let deck = Deck::with_special_cards(
+            &mut ChaCha8Rng::seed_from_u64(7),
+            &[SpecialCard::Bomb, SpecialCard::Cloud(None)],
+        );
+
+        assert_eq!(62, deck.cards.len());
+        assert!(deck.cards.contains(&Card::SpecialCard(SpecialCard::Bomb)));
+        assert!(deck
+            .cards
+            .contains(&Card::SpecialCard(SpecialCard::Cloud(None))));
+    }
+
+    #[test]
+    fn deck_from_spec() {
+        let spec = DeckSpec {
+            wizards: 2,
+            jesters: 0,
+            suits: vec![Suit::Heart, Suit::Spade],
+            removed_ranks: vec![Rank::Two, Rank::Three],
+            extra_ranks: Vec::new(),
+        };
+        let deck = Deck::from_spec(&mut ChaCha8Rng::seed_from_u64(7), &spec, &[]);
+
+        assert_eq!(24, spec.len());
+        assert_eq!(24, deck.cards.len());
+        assert!(!deck.cards.contains(&Card::SpecialCard(SpecialCard::Jester)));
+        assert!(deck.cards.iter().all(|card| match card {
+            Card::NormalCard(normal_card) => {
+                normal_card.rank != Rank::Two && normal_card.suit != Suit::Club
+            }
+            Card::SpecialCard(special_card) => *special_card == SpecialCard::Wizard,
+        }));
+        assert_eq!(60, DeckSpec::default().len());
+    }
+
+    #[test]
+    fn deck_with_extra_suits_and_ranks() {
+        let spec = DeckSpec {
+            suits: Suit::iter().chain(Suit::EXTRA).collect(),
+            extra_ranks: vec![Rank::One, Rank::Fifteen],
+            ..Default::default()
+        };
+        let deck = Deck::from_spec(&mut ChaCha8Rng::seed_from_u64(7), &spec, &[]);
+
+        // Six suits of 15 ranks plus the Wizards and Jesters
+        assert_eq!(98, spec.len());
+        assert_eq!(98, deck.cards.len());
+        let normal_cards: HashSet<&Card> = deck.cards.iter().collect();
+        assert_eq!(90 + 2, normal_cards.len());
+        assert!(deck.cards.contains(&Card::NormalCard(NormalCard {
+            suit: Suit::Leaf,
+            rank: Rank::Fifteen
+        })));
+        assert!(deck.cards.contains(&Card::NormalCard(NormalCard {
+            suit: Suit::Moon,
+            rank: Rank::One
+        })));
+    }
+
     #[test]
     fn to_and_from_json() {
         let deck = Deck::new();
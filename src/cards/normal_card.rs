@@ -2,6 +2,8 @@ use crate::cards::rank::Rank;
 use crate::cards::suit::Suit;
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::str::FromStr;
+use strum::IntoEnumIterator;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct NormalCard {
@@ -15,6 +17,12 @@ impl fmt::Display for NormalCard {
             f,
             "{}{}",
             match self.rank {
+                Rank::One => {
+                    "1"
+                }
+                Rank::Fifteen => {
+                    "15"
+                }
                 Rank::Ace => {
                     "A"
                 }
@@ -68,7 +76,31 @@ impl fmt::Display for NormalCard {
                 Suit::Heart => {
                     "H"
                 }
+                Suit::Moon => {
+                    "M"
+                }
+                Suit::Leaf => {
+                    "L"
+                }
             }
         )
     }
 }
+
+impl FromStr for NormalCard {
+    type Err = String;
+
+    /// Parse a card written like `AH` or `10C`, including extra suits and ranks such as `15M`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let suit = s
+            .chars()
+            .last()
+            .and_then(Suit::from_letter)
+            .ok_or_else(|| format!("No suit in card {:?}", s))?;
+        Rank::iter()
+            .chain(Rank::EXTRA)
+            .map(|rank| NormalCard { suit, rank })
+            .find(|card| card.to_string() == s)
+            .ok_or_else(|| format!("No rank in card {:?}", s))
+    }
+}
@@ -4,6 +4,9 @@ use strum_macros::EnumIter;
 
 #[derive(Copy, Clone, Debug, EnumIter, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Rank {
+    /// Extra rank below Two. Not part of `Rank::iter()`, which gives the standard 13.
+    #[strum(disabled)]
+    One,
     Two,
     Three,
     Four,
@@ -17,11 +20,18 @@ pub enum Rank {
     Queen,
     King,
     Ace,
+    /// Extra rank above Ace. Not part of `Rank::iter()`, which gives the standard 13.
+    #[strum(disabled)]
+    Fifteen,
 }
 
 impl Rank {
-    fn value(&self) -> u8 {
+    /// Ranks a deck can add to the standard 13
+    pub const EXTRA: [Rank; 2] = [Rank::One, Rank::Fifteen];
+
+    pub fn value(&self) -> u8 {
         match self {
+            Rank::One => 1,
             Rank::Two => 2,
             Rank::Three => 3,
             Rank::Four => 4,
@@ -35,13 +45,14 @@ impl Rank {
             Rank::Queen => 12,
             Rank::King => 13,
             Rank::Ace => 14,
+            Rank::Fifteen => 15,
         }
     }
 }
 
 impl PartialOrd for Rank {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.value().partial_cmp(&other.value())
+        Some(self.cmp(other))
     }
 }
 
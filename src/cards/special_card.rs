@@ -1,8 +1,137 @@
-use serde::{Deserialize, Serialize};
+use crate::cards::suit::Suit;
+use serde::de::{self, Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
 use strum_macros::EnumIter;
 
-#[derive(Copy, Clone, Debug, EnumIter, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Cards without a rank
+///
+/// Every card except `Wizard` and `Jester` is from the 25th anniversary edition and is only in
+/// the deck when a game is played with it.
+#[derive(Copy, Clone, Debug, EnumIter, PartialEq, Eq, Hash)]
 pub enum SpecialCard {
+    /// Wins the trick unless a Dragon is played
     Wizard,
+    /// Loses the trick unless only Jesters are played
     Jester,
+    /// Nobody wins a trick with a Bomb in it
+    Bomb,
+    /// Beats every card, even Wizards, unless a Fairy is in the same trick
+    Dragon,
+    /// Loses like a Jester, but wins the trick if a Dragon is played
+    Fairy,
+    /// Swapped with the trump card before bidding. Its holder picks the trump suit.
+    Werewolf,
+    /// Played as a 7½ of the suit the player chooses. Each player passes a card to the left after
+    /// the trick.
+    Juggler(Option<Suit>),
+    /// Played as a 9¾ of the suit the player chooses. The trick winner must raise or lower their
+    /// bid by one.
+    Cloud(Option<Suit>),
+}
+
+impl SpecialCard {
+    /// Whether the card is only in the deck when playing with the anniversary edition cards
+    pub fn is_expansion(&self) -> bool {
+        !matches!(self, SpecialCard::Wizard | SpecialCard::Jester)
+    }
+}
+
+impl fmt::Display for SpecialCard {
+    /// Name of the card. Juggler and Cloud are followed by the letter of their chosen suit.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpecialCard::Wizard => write!(f, "Wizard"),
+            SpecialCard::Jester => write!(f, "Jester"),
+            SpecialCard::Bomb => write!(f, "Bomb"),
+            SpecialCard::Dragon => write!(f, "Dragon"),
+            SpecialCard::Fairy => write!(f, "Fairy"),
+            SpecialCard::Werewolf => write!(f, "Werewolf"),
+            SpecialCard::Juggler(suit) => {
+                write!(f, "Juggler")?;
+                match suit {
+                    Some(suit) => write!(f, "{}", suit.letter()),
+                    None => Ok(()),
+                }
+            }
+            SpecialCard::Cloud(suit) => {
+                write!(f, "Cloud")?;
+                match suit {
+                    Some(suit) => write!(f, "{}", suit.letter()),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for SpecialCard {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let suited = |name: &str, card: fn(Option<Suit>) -> SpecialCard| {
+            let letter = s.strip_prefix(name)?;
+            match letter.chars().collect::<Vec<char>>()[..] {
+                [] => Some(card(None)),
+                [letter] => Suit::from_letter(letter).map(|suit| card(Some(suit))),
+                _ => None,
+            }
+        };
+
+        match s {
+            "Wizard" => Some(SpecialCard::Wizard),
+            "Jester" => Some(SpecialCard::Jester),
+            "Bomb" => Some(SpecialCard::Bomb),
+            "Dragon" => Some(SpecialCard::Dragon),
+            "Fairy" => Some(SpecialCard::Fairy),
+            "Werewolf" => Some(SpecialCard::Werewolf),
+            _ => suited("Juggler", SpecialCard::Juggler)
+                .or_else(|| suited("Cloud", SpecialCard::Cloud)),
+        }
+        .ok_or_else(|| format!("Unknown special card {:?}", s))
+    }
+}
+
+impl Serialize for SpecialCard {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for SpecialCard {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn names_round_trip() {
+        for card in SpecialCard::iter() {
+            assert_eq!(Ok(card), card.to_string().parse());
+        }
+        assert_eq!(
+            Ok(SpecialCard::Cloud(Some(Suit::Heart))),
+            "CloudH".parse::<SpecialCard>()
+        );
+        assert_eq!(
+            "JugglerS",
+            SpecialCard::Juggler(Some(Suit::Spade)).to_string()
+        );
+        assert!("CloudX".parse::<SpecialCard>().is_err());
+        assert!("Cloudy".parse::<SpecialCard>().is_err());
+    }
 }
@@ -7,4 +7,39 @@ pub enum Suit {
     Diamond,
     Spade,
     Heart,
+    /// Extra suit for larger decks. Not part of `Suit::iter()`, which gives the standard four.
+    #[strum(disabled)]
+    Moon,
+    /// Extra suit for larger decks. Not part of `Suit::iter()`, which gives the standard four.
+    #[strum(disabled)]
+    Leaf,
+}
+
+impl Suit {
+    /// Suits a deck can add to the standard four
+    pub const EXTRA: [Suit; 2] = [Suit::Moon, Suit::Leaf];
+
+    /// Letter used for the suit in card names, e.g. the `H` in `AH`
+    pub fn letter(&self) -> char {
+        match self {
+            Suit::Club => 'C',
+            Suit::Diamond => 'D',
+            Suit::Spade => 'S',
+            Suit::Heart => 'H',
+            Suit::Moon => 'M',
+            Suit::Leaf => 'L',
+        }
+    }
+
+    pub fn from_letter(letter: char) -> Option<Suit> {
+        match letter {
+            'C' => Some(Suit::Club),
+            'D' => Some(Suit::Diamond),
+            'S' => Some(Suit::Spade),
+            'H' => Some(Suit::Heart),
+            'M' => Some(Suit::Moon),
+            'L' => Some(Suit::Leaf),
+            _ => None,
+        }
+    }
 }
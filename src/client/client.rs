@@ -1,122 +1,203 @@
 use crate::cards::card::Card;
 use crate::cards::suit::Suit;
-use crate::network::action::Action;
-use crate::network::network::{
-    network_listener, serialize_and_write_to_network, wait_for_incoming_connection,
+use crate::game::event::GameEvent;
+use crate::network::action::{
+    Action, AdjustBidRequest, BidRequest, ChooseTrumpRequest, ClientAction, ErrorReason,
+    PassCardRequest, PlayCardRequest,
 };
-use local_ip_address::local_ip;
-use serde_json::{json, Value};
-use std::net::{TcpListener, TcpStream};
+use crate::network::connection::Connection;
+use std::io::ErrorKind;
+use std::thread;
+use std::time::Duration;
 
-/// Connect to the server
-///
-/// # Returns
-/// * Connection to read data from the server
-/// * Connection to write data to the server
-fn connect_to_server(host: &str, port: &str) -> std::io::Result<(TcpStream, TcpStream)> {
-    // Server writer
-    let mut server_writer = TcpStream::connect(format!("{}:{}", host, port))?;
+/// Number of times a client tries to rejoin after losing its connection
+const REJOIN_ATTEMPTS: usize = 5;
+/// Time between attempts to rejoin
+const REJOIN_DELAY: Duration = Duration::from_secs(1);
 
-    // Server listener
-    let listener = TcpListener::bind("0.0.0.0:0")?; // Let OS decide port
-    let port = listener.local_addr()?.port();
-    let host = local_ip().unwrap();
-
-    // Send over port to server
-    let port_json = json!({
-        "action": Action::Connect,
-        "port": port,
-        "host": host,
+/// Connect to the server and join as `name`, rejoining a seat when `session` is given
+fn join(host: &str, port: &str, name: &str, session: Option<&str>) -> std::io::Result<Connection> {
+    let mut server = Connection::connect(host, port)?;
+    server.send(&ClientAction::Join {
+        name: name.to_string(),
+        session: session.map(str::to_string),
     });
-    serialize_and_write_to_network(&mut server_writer, &port_json);
-
-    // Wait for incoming connection
-    loop {
-        match wait_for_incoming_connection(&listener) {
-            Ok(server_reader) => {
-                return Ok((server_reader, server_writer));
-            }
-            Err(e) => {
-                eprintln!("Error in server read connection: {}", e);
-            }
-        }
-    }
+    Ok(server)
 }
 
-// TODO: Create a struct for the arguments to functions like bid containing the state, etc. so the
-// client interfaces don't have to parse them
 pub trait Client {
     /// Create a client
-    fn new() -> Self;
+    fn new() -> Self
+    where
+        Self: Sized;
 
     /// Bid for this hand
-    fn bid(&mut self, json: &Value) -> u8;
+    fn bid(&mut self, request: &BidRequest) -> u8;
 
     /// Picks a trump suit
-    fn choose_trump(&mut self, json: &Value) -> Suit;
+    fn choose_trump(&mut self, request: &ChooseTrumpRequest) -> Suit;
+
+    /// Pick a card from `playable_cards`. A Juggler or Cloud must be given a suit.
+    fn play_card(&mut self, request: &PlayCardRequest) -> Card;
+
+    /// Raise (1) or lower (-1) the bid after winning a trick with a Cloud
+    fn adjust_bid(&mut self, request: &AdjustBidRequest) -> i8;
+
+    /// Pick a card from the hand to pass to the player on the left
+    fn pass_card(&mut self, request: &PassCardRequest) -> Card;
 
-    /// Pick a card from "playable_cards"
-    fn play_card(&mut self, json: &Value) -> Card;
+    /// Something happened in the game. Events are redacted, so they never show another hand.
+    fn observe(&mut self, _event: &GameEvent) {}
 
-    fn client(&mut self, host: &str, port: &str) -> std::io::Result<()> {
+    /// Join the game hosted at `host`:`port` as `name` and play until the game ends
+    ///
+    /// If the connection drops after joining, the client rejoins its seat with the session the
+    /// server gave it.
+    fn client(&mut self, host: &str, port: &str, name: &str) -> std::io::Result<()> {
+        self.lobby_client(host, port, name, None)
+    }
+
+    /// Join the server hosted at `host`:`port` as `name`, sit down with `table`, then play until
+    /// the game ends
+    ///
+    /// `table` is the `ClientAction::CreateTable` or `ClientAction::JoinTable` sent to a lobby
+    /// after joining. A server hosting a single game seats clients without one.
+    fn lobby_client(
+        &mut self,
+        host: &str,
+        port: &str,
+        name: &str,
+        table: Option<ClientAction>,
+    ) -> std::io::Result<()> {
         println!("Connecting to the server");
-        let (mut server_reader_stream, mut server_writer_stream) = connect_to_server(host, port)?;
-        println!("Read/Write connection to the server established.");
+        let mut server = join(host, port, name, None)?;
+        if let Some(table) = &table {
+            server.send(table);
+        }
+        let mut session: Option<String> = None;
+        // Rejoin attempts since the server last resumed our seat
+        let mut rejoins = 0;
 
         loop {
-            use crate::network::action::Action::*;
-
-            if let Ok((action, json)) = network_listener(&mut server_reader_stream) {
-                match action {
-                    Bid => {
-                        let bid = self.bid(&json);
-                        println!("Randomly bidding: {}", bid);
-                        let bid_json = json!({
-                            "action": Action::Bid,
-                            "bid": bid,
-                        });
-                        serialize_and_write_to_network(&mut server_writer_stream, &bid_json);
-                    }
-                    ChooseTrump => {
-                        let trump_suit = self.choose_trump(&json);
-                        println!("Randomly picking trump: {:?}", trump_suit);
-                        let trump_json = json!({
-                            "action": Action::ChooseTrump,
-                            "trump": trump_suit,
-                        });
-                        serialize_and_write_to_network(&mut server_writer_stream, &trump_json);
+            let action = match server.receive::<Action>() {
+                Ok(action) => action,
+                Err(e) => {
+                    eprintln!("Lost connection to the server: {}", e);
+                    match &session {
+                        Some(session) if rejoins < REJOIN_ATTEMPTS => {
+                            rejoins += 1;
+                            thread::sleep(REJOIN_DELAY);
+                            println!("Rejoining the game ({}/{})", rejoins, REJOIN_ATTEMPTS);
+                            match join(host, port, name, Some(session)) {
+                                Ok(rejoined) => server = rejoined,
+                                Err(e) => eprintln!("Failed to rejoin: {}", e),
+                            }
+                            continue;
+                        }
+                        _ => {
+                            return Err(std::io::Error::new(
+                                ErrorKind::ConnectionAborted,
+                                e.to_string(),
+                            ))
+                        }
                     }
-                    Confirmation => {
-                        println!(
-                            "Read connection from the server established. Json: {:#?}",
-                            json
-                        );
-                    }
-                    Connect => {
-                        todo!();
+                }
+            };
+
+            match action {
+                Action::AdjustBid(request) => {
+                    let adjustment = self.adjust_bid(&request);
+                    println!("Adjusting bid by: {}", adjustment);
+                    server.send(&ClientAction::AdjustBid { adjustment });
+                }
+                Action::Bid(request) => {
+                    let bid = self.bid(&request);
+                    println!("Bidding: {}", bid);
+                    server.send(&ClientAction::Bid { bid });
+                }
+                Action::BidsRevealed(revealed) => {
+                    for placed in revealed.bids.iter() {
+                        println!("Player {} bid {}", placed.player, placed.bid);
                     }
-                    Card => {
-                        todo!();
+                }
+                Action::ChooseTrump(request) => {
+                    let trump = self.choose_trump(&request);
+                    println!("Picking trump: {:?}", trump);
+                    server.send(&ClientAction::ChooseTrump { trump });
+                }
+                Action::Confirmation(confirmation) => {
+                    println!(
+                        "Connection to the server established. Message: {}",
+                        confirmation.msg
+                    );
+                    if confirmation.session.is_some() {
+                        session = confirmation.session;
                     }
-                    EndGame => {
-                        println!("Game has ended. Final Game State: {:#?}", json);
-                        break;
+                }
+                Action::Error(error) => {
+                    eprintln!(
+                        "Server rejected response ({:?}): {}",
+                        error.reason, error.msg
+                    );
+                    // There is no game to play without a table
+                    if matches!(
+                        error.reason,
+                        ErrorReason::InvalidTable | ErrorReason::TableUnavailable
+                    ) {
+                        return Err(std::io::Error::other(error.msg));
                     }
-                    PlayCard => {
-                        let played_card = self.play_card(&json);
-                        println!("Randomly playing: {:?}", played_card);
-                        let played_card_json = json!({
-                            "action": Action::PlayCard,
-                            "played_card": played_card,
-                        });
-                        serialize_and_write_to_network(
-                            &mut server_writer_stream,
-                            &played_card_json,
+                }
+                // Redacted events, sent to players and spectators as the game goes on
+                Action::Event(message) => self.observe(&message.event),
+                Action::EndGame(end_game) => {
+                    println!("Game has ended. Final Game State: {:#?}", end_game.state);
+                    break;
+                }
+                Action::PassCard(request) => {
+                    let card = self.pass_card(&request);
+                    println!("Passing: {:?}", card);
+                    server.send(&ClientAction::PassCard { card });
+                }
+                Action::PlayCard(request) => {
+                    let played_card = self.play_card(&request);
+                    println!("Playing: {:?}", played_card);
+                    server.send(&ClientAction::PlayCard { played_card });
+                }
+                Action::Resume(resume) => {
+                    rejoins = 0;
+                    println!(
+                        "Rejoined seat {} holding {:?}. Game state: {:#?}",
+                        resume.seat, resume.hand, resume.state
+                    );
+                }
+                Action::StartGame(start_game) => {
+                    println!(
+                        "Starting the game. Initial game state: {:#?}",
+                        start_game.state
+                    );
+                }
+                Action::TableJoined(joined) => {
+                    println!(
+                        "Sat down at table {} with {:?}. Waiting for {} players",
+                        joined.table.id, joined.table.players, joined.table.num_players
+                    );
+                }
+                Action::TableLeft(left) => {
+                    println!("Left table {}", left.table);
+                }
+                Action::Tables(list) => {
+                    for table in list.tables.iter() {
+                        println!(
+                            "Table {}: {}/{} players {:?}",
+                            table.id,
+                            table.players.len(),
+                            table.num_players,
+                            table.players
                         );
                     }
-                    StartGame => {
-                        println!("Starting the game. Initial game state: {:#?}", json);
-                    }
+                }
+                Action::TurnTimedOut(timed_out) => {
+                    println!("{}", timed_out.msg);
                 }
             }
         }
@@ -0,0 +1,333 @@
+use crate::client::ismcts_client::SearchBudget;
+use crate::client::process_client::DEFAULT_TIMEOUT;
+use crate::game::rules::GameRules;
+use crate::network::action::ClientAction;
+use anyhow::{Context, Result};
+use clap::{Parser, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Which `Client` implementation makes the decisions
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClientKind {
+    Random,
+    Human,
+    /// Bot that bids and plays to make its bid
+    Heuristic,
+    /// Bot that searches sampled deals before every bid and card
+    Ismcts,
+    /// Bot running as a separate program, see `docs/bot_protocol.md`
+    External,
+    /// Watch without a seat
+    Spectator,
+}
+
+/// Command line arguments for the client binary
+///
+/// Any argument that is given overrides the value from the config file.
+#[derive(Clone, Debug, Default, Parser)]
+#[command(about = "Join a game of Wizard", long_about = None)]
+pub struct ClientArgs {
+    /// TOML file to read the client config from
+    #[arg(short, long)]
+    pub config: Option<PathBuf>,
+    /// Address of the server
+    #[arg(long)]
+    pub host: Option<String>,
+    /// Port of the server
+    #[arg(short, long)]
+    pub port: Option<u16>,
+    /// Name shown to the other players
+    #[arg(long)]
+    pub name: Option<String>,
+    /// Who makes the decisions for this client
+    #[arg(long, value_enum)]
+    pub client: Option<ClientKind>,
+    /// Seed for bots that make random decisions
+    #[arg(long)]
+    pub seed: Option<u64>,
+    /// Deals the search bot plays out before each decision
+    #[arg(long, conflicts_with = "think_ms")]
+    pub iterations: Option<u32>,
+    /// Milliseconds the search bot thinks before each decision
+    #[arg(long)]
+    pub think_ms: Option<u64>,
+    /// Program to start as the external bot
+    #[arg(long)]
+    pub bot: Option<String>,
+    /// Argument for the external bot program, can be given more than once
+    #[arg(long = "bot-arg", allow_hyphen_values = true)]
+    pub bot_args: Vec<String>,
+    /// Milliseconds the external bot has to answer each request
+    #[arg(long)]
+    pub bot_timeout_ms: Option<u64>,
+    /// Open a lobby table with this many seats and sit down at it
+    #[arg(long, conflicts_with = "join_table")]
+    pub create_table: Option<usize>,
+    /// Sit down at this lobby table, or watch it as a spectator
+    #[arg(long)]
+    pub join_table: Option<u64>,
+    /// Server's admin token, for spectators to see every hand
+    #[arg(long)]
+    pub admin_token: Option<String>,
+    /// File a spectator writes the events it saw to once the game ends
+    #[arg(long)]
+    pub record: Option<PathBuf>,
+}
+
+/// Client settings
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClientConfig {
+    pub host: String,
+    pub port: u16,
+    pub name: String,
+    pub client: ClientKind,
+    /// Random when not set
+    pub seed: Option<u64>,
+    /// Deals the search bot plays out before each decision. Takes priority over `think_ms`.
+    pub iterations: Option<u32>,
+    /// Milliseconds the search bot thinks before each decision
+    pub think_ms: Option<u64>,
+    /// Program to start as the external bot
+    pub bot: Option<String>,
+    /// Arguments for the external bot program
+    pub bot_args: Vec<String>,
+    /// Milliseconds the external bot has to answer each request
+    pub bot_timeout_ms: Option<u64>,
+    /// Seats at the lobby table to open. Takes priority over `join_table`.
+    pub create_table: Option<usize>,
+    /// Lobby table to sit down at, or to watch as a spectator
+    pub join_table: Option<u64>,
+    /// Rules of the lobby table opened with `create_table`
+    pub table_rules: GameRules,
+    /// Server's admin token, for spectators to see every hand
+    pub admin_token: Option<String>,
+    /// File a spectator writes the events it saw to once the game ends
+    pub record: Option<PathBuf>,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        ClientConfig {
+            host: "0.0.0.0".to_string(),
+            port: 7878,
+            name: "player".to_string(),
+            client: ClientKind::Random,
+            seed: None,
+            iterations: None,
+            think_ms: None,
+            bot: None,
+            bot_args: Vec::new(),
+            bot_timeout_ms: None,
+            create_table: None,
+            join_table: None,
+            table_rules: GameRules::default(),
+            admin_token: None,
+            record: None,
+        }
+    }
+}
+
+impl ClientConfig {
+    /// Read a config file. Missing fields keep their default values.
+    pub fn from_file(path: &Path) -> Result<ClientConfig> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        toml::from_str(&contents).with_context(|| format!("Invalid config file {}", path.display()))
+    }
+
+    /// Build the config from the config file, if one was given, then apply command line overrides
+    pub fn from_args(args: ClientArgs) -> Result<ClientConfig> {
+        let mut config = match &args.config {
+            Some(path) => ClientConfig::from_file(path)?,
+            None => ClientConfig::default(),
+        };
+
+        if let Some(host) = args.host {
+            config.host = host;
+        }
+        if let Some(port) = args.port {
+            config.port = port;
+        }
+        if let Some(name) = args.name {
+            config.name = name;
+        }
+        if let Some(client) = args.client {
+            config.client = client;
+        }
+        if args.seed.is_some() {
+            config.seed = args.seed;
+        }
+        if args.iterations.is_some() {
+            config.iterations = args.iterations;
+            config.think_ms = None;
+        }
+        if args.think_ms.is_some() {
+            config.think_ms = args.think_ms;
+            config.iterations = None;
+        }
+        if args.bot.is_some() {
+            config.bot = args.bot;
+        }
+        if !args.bot_args.is_empty() {
+            config.bot_args = args.bot_args;
+        }
+        if args.bot_timeout_ms.is_some() {
+            config.bot_timeout_ms = args.bot_timeout_ms;
+        }
+        if args.create_table.is_some() {
+            config.create_table = args.create_table;
+            config.join_table = None;
+        }
+        if args.join_table.is_some() {
+            config.join_table = args.join_table;
+            config.create_table = None;
+        }
+        if args.admin_token.is_some() {
+            config.admin_token = args.admin_token;
+        }
+        if args.record.is_some() {
+            config.record = args.record;
+        }
+
+        Ok(config)
+    }
+
+    /// How long the search bot thinks before each decision
+    pub fn search_budget(&self) -> SearchBudget {
+        match (self.iterations, self.think_ms) {
+            (Some(iterations), _) => SearchBudget::Iterations(iterations),
+            (None, Some(ms)) => SearchBudget::Time(Duration::from_millis(ms)),
+            (None, None) => SearchBudget::default(),
+        }
+    }
+
+    /// Time the external bot has to answer each request
+    pub fn bot_timeout(&self) -> Duration {
+        self.bot_timeout_ms
+            .map_or(DEFAULT_TIMEOUT, Duration::from_millis)
+    }
+
+    /// Request to sit down at a lobby table, if the client should send one
+    pub fn table_action(&self) -> Option<ClientAction> {
+        match (self.create_table, self.join_table) {
+            (Some(num_players), _) => Some(ClientAction::CreateTable {
+                num_players,
+                rules: self.table_rules.clone(),
+            }),
+            (None, Some(table)) => Some(ClientAction::JoinTable { table }),
+            (None, None) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_args() {
+        let config = ClientConfig::from_args(ClientArgs::parse_from([
+            "client", "--client", "human", "--name", "Merlin", "-p", "9000",
+        ]))
+        .unwrap();
+
+        assert_eq!(ClientKind::Human, config.client);
+        assert_eq!("Merlin", config.name);
+        assert_eq!(9000, config.port);
+        assert_eq!("0.0.0.0", config.host);
+    }
+
+    #[test]
+    fn parse_config_file() {
+        let config: ClientConfig = toml::from_str("client = \"random\"\nname = \"Bot\"").unwrap();
+
+        assert_eq!(ClientKind::Random, config.client);
+        assert_eq!("Bot", config.name);
+        assert_eq!(7878, config.port);
+    }
+
+    #[test]
+    fn lobby_table_from_args() {
+        let config =
+            ClientConfig::from_args(ClientArgs::parse_from(["client", "--join-table", "3"]))
+                .unwrap();
+        assert_eq!(
+            Some(ClientAction::JoinTable { table: 3 }),
+            config.table_action()
+        );
+
+        let config =
+            ClientConfig::from_args(ClientArgs::parse_from(["client", "--create-table", "4"]))
+                .unwrap();
+        assert_eq!(
+            Some(ClientAction::CreateTable {
+                num_players: 4,
+                rules: GameRules::default(),
+            }),
+            config.table_action()
+        );
+        assert_eq!(None, ClientConfig::default().table_action());
+    }
+
+    #[test]
+    fn search_budget_from_config_file_or_args() {
+        let path = std::env::temp_dir().join(format!(
+            "wizard_client_search_budget_{}.toml",
+            std::process::id()
+        ));
+        std::fs::write(&path, "client = \"ismcts\"\niterations = 200").unwrap();
+        let config_arg = path.to_str().unwrap();
+
+        let config =
+            ClientConfig::from_args(ClientArgs::parse_from(["client", "-c", config_arg])).unwrap();
+        assert_eq!(ClientKind::Ismcts, config.client);
+        assert_eq!(SearchBudget::Iterations(200), config.search_budget());
+
+        let config = ClientConfig::from_args(ClientArgs::parse_from([
+            "client",
+            "-c",
+            config_arg,
+            "--think-ms",
+            "250",
+        ]))
+        .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            SearchBudget::Time(Duration::from_millis(250)),
+            config.search_budget()
+        );
+        assert_eq!(
+            SearchBudget::default(),
+            ClientConfig::default().search_budget()
+        );
+    }
+
+    #[test]
+    fn external_bot_from_args() {
+        let config = ClientConfig::from_args(ClientArgs::parse_from([
+            "client",
+            "--client",
+            "external",
+            "--bot",
+            "python3",
+            "--bot-arg",
+            "bot.py",
+            "--bot-arg",
+            "--fast",
+            "--bot-timeout-ms",
+            "500",
+        ]))
+        .unwrap();
+
+        assert_eq!(ClientKind::External, config.client);
+        assert_eq!(Some("python3".to_string()), config.bot);
+        assert_eq!(vec!["bot.py", "--fast"], config.bot_args);
+        assert_eq!(Duration::from_millis(500), config.bot_timeout());
+        assert_eq!(DEFAULT_TIMEOUT, ClientConfig::default().bot_timeout());
+    }
+}
@@ -0,0 +1,361 @@
+use crate::cards::card::Card;
+use crate::cards::rank::Rank;
+use crate::cards::special_card::SpecialCard;
+use crate::cards::suit::Suit;
+use crate::client::client::Client;
+use crate::game::engine::GameEngine;
+use crate::game::state::GameState;
+use crate::network::action::{
+    AdjustBidRequest, BidRequest, ChooseTrumpRequest, PassCardRequest, PlayCardRequest,
+};
+use strum::IntoEnumIterator;
+
+/// Bot that bids the tricks its hand should take, then plays to take exactly that many
+///
+/// Bids count Wizards, high trumps, side aces and kings, and voids it can trump into. While
+/// playing it tries to win tricks until its bid is made, then gets rid of its highest cards that
+/// lose.
+#[derive(Default)]
+pub struct HeuristicClient {
+    /// Seat the bot is playing in, once it has been asked to bid or play
+    seat: Option<usize>,
+}
+
+impl HeuristicClient {
+    /// Play for `seat`, for when the bot has not been asked to bid or play yet
+    pub(crate) fn set_seat(&mut self, seat: usize) {
+        self.seat = Some(seat);
+    }
+
+    /// Tricks still needed to make the bid. Negative once the bid has been passed.
+    fn tricks_needed(&self, state: &GameState) -> i16 {
+        match self.seat.and_then(|seat| state.players.get(seat)) {
+            Some(player) => player.bid.unwrap_or(0) as i16 - player.tricks_taken as i16,
+            None => 0,
+        }
+    }
+}
+
+/// Number of tricks `hand` is expected to take
+pub(crate) fn expected_tricks(hand: &[Card], trump_suit: Option<Suit>, player_count: usize) -> f32 {
+    let players = player_count.max(2) as f32;
+    let trumps = hand
+        .iter()
+        .filter(|card| suit_of(card).is_some() && suit_of(card) == trump_suit)
+        .count();
+
+    let mut tricks = 0.0;
+    let mut low_trumps = 0;
+    for card in hand.iter() {
+        tricks += match card {
+            Card::SpecialCard(SpecialCard::Dragon) => 1.0,
+            Card::SpecialCard(SpecialCard::Wizard) => 0.95,
+            Card::SpecialCard(SpecialCard::Cloud(_)) => 0.35,
+            Card::SpecialCard(SpecialCard::Juggler(_)) => 0.2,
+            Card::SpecialCard(_) => 0.0,
+            Card::NormalCard(card) if Some(card.suit) == trump_suit => match card.rank {
+                Rank::Ace | Rank::King | Rank::Queen => 0.85,
+                Rank::Jack | Rank::Ten => 0.55,
+                _ if trumps >= 3 => {
+                    low_trumps += 1;
+                    0.3
+                }
+                _ => {
+                    low_trumps += 1;
+                    0.1
+                }
+            },
+            // Side cards are less likely to hold up the more players there are
+            Card::NormalCard(card) => match card.rank {
+                Rank::Ace => (2.4 / players).min(0.9),
+                Rank::King => (1.2 / players).min(0.5),
+                _ => 0.0,
+            },
+        };
+    }
+
+    // A low trump can take a trick in a side suit the hand has none of
+    if let Some(trump_suit) = trump_suit {
+        let voids = Suit::iter()
+            .filter(|suit| *suit != trump_suit && !hand.iter().any(|c| suit_of(c) == Some(*suit)))
+            .count();
+        tricks += 0.25 * voids.min(low_trumps) as f32;
+    }
+
+    tricks
+}
+
+/// Suit a card follows, if it has one
+fn suit_of(card: &Card) -> Option<Suit> {
+    match card {
+        Card::NormalCard(card) => Some(card.suit),
+        Card::SpecialCard(SpecialCard::Juggler(suit) | SpecialCard::Cloud(suit)) => *suit,
+        Card::SpecialCard(_) => None,
+    }
+}
+
+/// How much the bot would rather keep a card, from least to most useful for taking tricks
+fn strength(card: &Card, trump_suit: Option<Suit>) -> u8 {
+    let value = match card {
+        Card::NormalCard(card) => card.rank.value(),
+        Card::SpecialCard(SpecialCard::Juggler(_)) => 7,
+        Card::SpecialCard(SpecialCard::Cloud(_)) => 9,
+        Card::SpecialCard(SpecialCard::Wizard) => return 40,
+        Card::SpecialCard(SpecialCard::Dragon) => return 41,
+        Card::SpecialCard(_) => return 0,
+    };
+    if suit_of(card).is_some() && suit_of(card) == trump_suit {
+        value + 15
+    } else {
+        value
+    }
+}
+
+/// Bid closest to `estimate` that is allowed
+pub(crate) fn closest_bid(estimate: f32, hand_size: u8, forbidden_bid: Option<u8>) -> u8 {
+    (0..=hand_size)
+        .filter(|bid| Some(*bid) != forbidden_bid)
+        .min_by(|a, b| {
+            let a = (*a as f32 - estimate).abs();
+            let b = (*b as f32 - estimate).abs();
+            a.total_cmp(&b)
+        })
+        .unwrap_or(0)
+}
+
+impl Client for HeuristicClient {
+    /// Create a heuristic client
+    fn new() -> HeuristicClient {
+        HeuristicClient::default()
+    }
+
+    /// Bids the tricks the hand is expected to take, or the closest allowed bid
+    fn bid(&mut self, request: &BidRequest) -> u8 {
+        self.seat = Some(request.next_bidder);
+        let estimate = expected_tricks(
+            &request.hand,
+            request.state.trump_suit,
+            request.state.player_count,
+        );
+        closest_bid(estimate, request.state.hand_size, request.forbidden_bid)
+    }
+
+    /// Picks the suit the hand holds the most and highest cards of
+    fn choose_trump(&mut self, request: &ChooseTrumpRequest) -> Suit {
+        Suit::iter()
+            .max_by_key(|suit| {
+                request
+                    .hand
+                    .iter()
+                    .filter_map(|card| match card {
+                        Card::NormalCard(card) if card.suit == *suit => {
+                            Some(5 + card.rank.value() as u32)
+                        }
+                        _ => None,
+                    })
+                    .sum::<u32>()
+            })
+            .unwrap_or(Suit::Heart)
+    }
+
+    /// Takes the trick while tricks are still needed, otherwise throws the highest card that
+    /// loses
+    ///
+    /// Playing last it wins with its cheapest winning card. Earlier in the trick it plays its
+    /// strongest card, since a cheap winner may still be beaten.
+    fn play_card(&mut self, request: &PlayCardRequest) -> Card {
+        let state = &request.state;
+        let played = &state.played_cards;
+        let player_count = state.player_count.max(1);
+        self.seat = Some((state.current_leader + played.len()) % player_count);
+
+        let candidates: Vec<Card> = request
+            .playable_cards
+            .iter()
+            .flat_map(|card| match card.needs_suit() {
+                true => Suit::iter()
+                    .map(|suit| card.with_chosen_suit(suit))
+                    .collect(),
+                false => vec![*card],
+            })
+            .collect();
+        let bombed = played.contains(&Card::SpecialCard(SpecialCard::Bomb));
+        let wins = |card: &Card| {
+            let mut trick = played.clone();
+            trick.push(*card);
+            !bombed
+                && *card != Card::SpecialCard(SpecialCard::Bomb)
+                && GameEngine::winning_card(&trick, state.trump_suit) == played.len()
+        };
+        let strength = |card: &&Card| strength(card, state.trump_suit);
+        let weakest = || *candidates.iter().min_by_key(strength).unwrap();
+
+        if self.tricks_needed(state) > 0 {
+            let winners = candidates.iter().filter(|card| wins(card));
+            let last_to_play = played.len() + 1 >= player_count;
+            let winner = match last_to_play {
+                true => winners.min_by_key(strength),
+                false => winners.max_by_key(strength),
+            };
+            winner.copied().unwrap_or_else(weakest)
+        } else {
+            candidates
+                .iter()
+                .filter(|card| !wins(card))
+                .max_by_key(strength)
+                .copied()
+                .unwrap_or_else(weakest)
+        }
+    }
+
+    /// Moves the bid towards the tricks the rest of the hand is expected to take
+    fn adjust_bid(&mut self, request: &AdjustBidRequest) -> i8 {
+        let taken = request.bid as i16 - self.tricks_needed(&request.state);
+        let target = taken as f32
+            + expected_tricks(
+                &request.hand,
+                request.state.trump_suit,
+                request.state.player_count,
+            );
+
+        let bid = request.bid as f32;
+        if request.bid == 0 || (request.bid as i16) <= taken || target > bid {
+            1
+        } else {
+            -1
+        }
+    }
+
+    /// Passes the weakest card while tricks are still needed, otherwise the strongest
+    fn pass_card(&mut self, request: &PassCardRequest) -> Card {
+        let strength = |card: &&Card| strength(card, request.state.trump_suit);
+        let card = if self.tricks_needed(&request.state) > 0 {
+            request.hand.iter().min_by_key(strength)
+        } else {
+            request.hand.iter().max_by_key(strength)
+        };
+        *card.unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::normal_card::NormalCard;
+    use crate::game::engine::Turn;
+    use crate::game::rules::GameRules;
+    use crate::game::state::PlayerState;
+    use crate::simulation::local_game::{play_local_game, Bot};
+
+    fn card(suit: Suit, rank: Rank) -> Card {
+        Card::NormalCard(NormalCard { suit, rank })
+    }
+
+    const WIZARD: Card = Card::SpecialCard(SpecialCard::Wizard);
+
+    fn state(trump_suit: Option<Suit>, played_cards: Vec<Card>, bid: u8) -> GameState {
+        GameState {
+            hand_size: 5,
+            trump_suit,
+            player_count: 3,
+            players: vec![
+                PlayerState {
+                    bid: Some(bid),
+                    ..Default::default()
+                };
+                3
+            ],
+            current_leader: (3 - played_cards.len()) % 3,
+            played_cards,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn bids_wizards_and_high_trumps() {
+        let hand = vec![
+            WIZARD,
+            WIZARD,
+            card(Suit::Heart, Rank::Ace),
+            card(Suit::Club, Rank::Three),
+            card(Suit::Diamond, Rank::Four),
+        ];
+        let mut request = BidRequest {
+            hand,
+            bids: Vec::new(),
+            next_bidder: 0,
+            forbidden_bid: None,
+            state: state(Some(Suit::Heart), Vec::new(), 0),
+        };
+        let mut client = HeuristicClient::new();
+
+        assert_eq!(3, client.bid(&request));
+
+        request.forbidden_bid = Some(3);
+        assert_eq!(2, client.bid(&request));
+    }
+
+    #[test]
+    fn wins_cheaply_when_tricks_are_needed() {
+        let hand = vec![card(Suit::Heart, Rank::King), card(Suit::Heart, Rank::Ten)];
+        let request = PlayCardRequest {
+            playable_cards: hand.clone(),
+            hand,
+            state: state(
+                Some(Suit::Club),
+                vec![card(Suit::Heart, Rank::Five), card(Suit::Heart, Rank::Nine)],
+                1,
+            ),
+        };
+
+        assert_eq!(
+            card(Suit::Heart, Rank::Ten),
+            HeuristicClient::new().play_card(&request)
+        );
+    }
+
+    #[test]
+    fn ducks_with_the_highest_losing_card_once_the_bid_is_made() {
+        let hand = vec![
+            card(Suit::Heart, Rank::King),
+            card(Suit::Heart, Rank::Jack),
+            card(Suit::Heart, Rank::Two),
+        ];
+        let request = PlayCardRequest {
+            playable_cards: hand.clone(),
+            hand,
+            state: state(
+                Some(Suit::Club),
+                vec![
+                    card(Suit::Heart, Rank::Queen),
+                    card(Suit::Heart, Rank::Nine),
+                ],
+                0,
+            ),
+        };
+
+        assert_eq!(
+            card(Suit::Heart, Rank::Jack),
+            HeuristicClient::new().play_card(&request)
+        );
+    }
+
+    #[test]
+    fn plays_legal_games_against_itself() {
+        let anniversary = GameRules {
+            special_cards: SpecialCard::iter()
+                .filter(|card| card.is_expansion())
+                .collect(),
+            ..Default::default()
+        };
+        for (seed, rules) in [(0, GameRules::default()), (1, anniversary)] {
+            let mut engine = GameEngine::with_rules(4, seed, rules).unwrap();
+            let mut bots: Vec<Bot> = (0..4)
+                .map(|_| Box::new(HeuristicClient::new()) as Bot)
+                .collect();
+
+            play_local_game(&mut engine, &mut bots).unwrap();
+            assert_eq!(Turn::GameOver, engine.turn());
+        }
+    }
+}
@@ -1,84 +1,128 @@
 use crate::cards::card::Card;
-use crate::cards::normal_card::NormalCard;
-use crate::cards::rank::Rank;
 use crate::cards::suit::Suit;
-use std::io::{self, Read, Write};
-use std::net::TcpStream;
-use std::thread;
+use crate::client::client::Client;
+use crate::game::state::GameState;
+use crate::network::action::{
+    AdjustBidRequest, BidRequest, ChooseTrumpRequest, PassCardRequest, PlayCardRequest,
+};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::io::{self, Write};
 
+/// Client that asks a person at the terminal for each decision
 pub struct HumanClient;
 
 impl HumanClient {
-    fn send_data(data: &Card) -> std::io::Result<()> {
-        let mut stream = TcpStream::connect("0.0.0.0:7878")?;
-        let serialized = serde_json::to_string(data).unwrap();
-        stream.write_all(serialized.as_bytes())?;
-        println!("Client sent {:?}", data);
-        Ok(())
+    /// Print the parts of the game state a person needs to make a decision
+    fn show_state(state: &GameState, hand: &[Card]) {
+        println!();
+        println!("Round {} of {}", state.round, state.num_rounds);
+        println!("Trump: {:?}", state.trump_suit);
+        for (i, player) in state.players.iter().enumerate() {
+            println!(
+                "  {} ({}): score {}, bid {:?}, tricks {}",
+                i, player.name, player.score, player.bid, player.tricks_taken
+            );
+        }
+        if !state.played_cards.is_empty() {
+            println!(
+                "Played cards: {}",
+                serde_json::to_string(&state.played_cards).unwrap()
+            );
+        }
+        println!("Your hand: {}", serde_json::to_string(hand).unwrap());
     }
 
-    pub fn client(&mut self) -> std::io::Result<()> {
-        let mut stream = TcpStream::connect("0.0.0.0:7878")?;
-        println!("Connected to the server.");
-
-        let mut stream_clone = stream.try_clone()?;
+    /// Ask for a line of input
+    fn read_line(message: &str) -> String {
+        print!("{}: ", message);
+        io::stdout().flush().unwrap();
+        let mut input = String::new();
+        if io::stdin().read_line(&mut input).unwrap() == 0 {
+            panic!("Standard input closed while waiting for a decision");
+        }
+        input.trim().to_string()
+    }
 
-        // Listen to server
-        thread::spawn(move || {
-            // TODO: Make like server
-            let mut buffer = [0; 512];
-            loop {
-                match stream_clone.read(&mut buffer) {
-                    Ok(0) => {
-                        println!("Server disconnected!");
-                        break;
-                    }
-                    Ok(bytes_read) => {
-                        let response = String::from_utf8_lossy(&buffer[..bytes_read]);
-                        println!("Received from server: {}", response);
-                    }
-                    Err(e) => {
-                        eprintln!("Error reading from server: {}", e);
-                        break;
-                    }
-                }
+    /// Keep asking until the input can be parsed and passes `is_valid`
+    ///
+    /// Input is parsed as a JSON string, so cards are entered as e.g. `AH` or `Wizard` and suits
+    /// as e.g. `Heart`.
+    fn prompt<T: DeserializeOwned>(message: &str, is_valid: impl Fn(&T) -> bool) -> T {
+        loop {
+            let input = HumanClient::read_line(message);
+            match serde_json::from_value(Value::String(input)) {
+                Ok(value) if is_valid(&value) => return value,
+                Ok(_) => eprintln!("Not allowed. Try again."),
+                Err(e) => eprintln!("Could not understand input: {}", e),
             }
-        });
-
-        let tmp = serde_json::to_string(&Card::NormalCard(NormalCard {
-            suit: Suit::Heart,
-            rank: Rank::Two,
-        }))
-        .unwrap();
-        println!("{}", tmp);
+        }
+    }
+}
 
-        // Send to server
-        'server_send: loop {
-            print!("Enter card: ");
-            io::stdout().flush()?;
-            let mut input = String::new();
-            io::stdin().read_line(&mut input)?;
-            let trimmed = input.trim();
+impl Client for HumanClient {
+    fn new() -> HumanClient {
+        HumanClient
+    }
 
-            let card: Card = match serde_json::from_str(trimmed) {
-                Ok(data) => data,
-                Err(e) => {
-                    eprintln!("Error sending to server: {}", e);
-                    continue;
+    fn bid(&mut self, request: &BidRequest) -> u8 {
+        HumanClient::show_state(&request.state, &request.hand);
+        for placed in request.bids.iter() {
+            println!("Player {} bid {}", placed.player, placed.bid);
+        }
+        println!("You are player {}", request.next_bidder);
+        if let Some(bid) = request.forbidden_bid {
+            println!("You may not bid {}", bid);
+        }
+        loop {
+            match HumanClient::read_line("Enter bid").parse::<u8>() {
+                Ok(bid) if Some(bid) == request.forbidden_bid => {
+                    eprintln!("Bid {} is not allowed", bid)
                 }
-            };
+                Ok(bid) if bid <= request.state.hand_size => return bid,
+                _ => eprintln!("Bid must be between 0 and {}", request.state.hand_size),
+            }
+        }
+    }
 
-            let serialized = serde_json::to_string(&card).unwrap();
+    fn choose_trump(&mut self, request: &ChooseTrumpRequest) -> Suit {
+        HumanClient::show_state(&request.state, &request.hand);
+        HumanClient::prompt("Choose trump (Club, Diamond, Spade, Heart)", |_| true)
+    }
 
-            // Send data in chunks
-            let chunk_size = 1024;
+    fn play_card(&mut self, request: &PlayCardRequest) -> Card {
+        HumanClient::show_state(&request.state, &request.hand);
+        println!(
+            "Playable cards: {}",
+            serde_json::to_string(&request.playable_cards).unwrap()
+        );
+        HumanClient::prompt(
+            "Enter card (play a Juggler or Cloud with a suit, e.g. CloudH)",
+            |card: &Card| {
+                !card.needs_suit() && request.playable_cards.contains(&card.without_chosen_suit())
+            },
+        )
+    }
 
-            for chunk in serialized.as_bytes().chunks(chunk_size) {
-                if let Err(e) = stream.write_all(chunk) {
-                    eprintln!("Error sending chunk: {}", e);
-                    continue 'server_send;
-                }
+    fn adjust_bid(&mut self, request: &AdjustBidRequest) -> i8 {
+        HumanClient::show_state(&request.state, &request.hand);
+        println!(
+            "You won a trick with a Cloud. Your bid of {} must change.",
+            request.bid
+        );
+        loop {
+            match HumanClient::read_line("Raise (1) or lower (-1) your bid").parse::<i8>() {
+                Ok(1) => return 1,
+                Ok(-1) if request.bid > 0 => return -1,
+                _ => eprintln!("Enter 1, or -1 if your bid is above 0"),
             }
         }
     }
+
+    fn pass_card(&mut self, request: &PassCardRequest) -> Card {
+        HumanClient::show_state(&request.state, &request.hand);
+        HumanClient::prompt("Enter card to pass to the left", |card| {
+            request.hand.contains(card)
+        })
+    }
 }
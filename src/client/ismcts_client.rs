@@ -0,0 +1,601 @@
+use crate::cards::card::Card;
+use crate::cards::deck::Deck;
+use crate::cards::special_card::SpecialCard;
+use crate::cards::suit::Suit;
+use crate::client::client::Client;
+use crate::client::heuristic_client::{closest_bid, expected_tricks, HeuristicClient};
+use crate::game::engine::{GameEngine, Turn};
+use crate::game::event::GameEvent;
+use crate::game::rules::GameRules;
+use crate::game::state::GameState;
+use crate::network::action::{
+    AdjustBidRequest, BidRequest, ChooseTrumpRequest, PassCardRequest, PlayCardRequest,
+};
+use anyhow::{bail, Result};
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::time::{Duration, Instant};
+use strum::IntoEnumIterator;
+
+/// Balance between trying moves that did well and moves that have not been tried much
+const EXPLORATION: f64 = 0.7;
+
+/// How long the bot searches before each bid or card
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SearchBudget {
+    /// Play out this many sampled rounds. Decisions are reproducible under a seed.
+    Iterations(u32),
+    /// Play out sampled rounds until the time is up. Decisions depend on the machine's speed.
+    Time(Duration),
+}
+
+impl Default for SearchBudget {
+    fn default() -> Self {
+        SearchBudget::Iterations(1000)
+    }
+}
+
+/// Bot that bids and plays by information set Monte Carlo tree search
+///
+/// Before each bid or card it deals the cards it can not see at random, consistent with every
+/// card played so far and the suits players have shown they are out of, and plays the rest of
+/// the round out from there. A single tree is shared by every sampled deal, so the bot only
+/// chooses between moves it could tell apart with what it knows. The move tried most often is
+/// played.
+///
+/// Trump, bid adjustments and passed cards are left to `HeuristicClient`.
+pub struct IsmctsClient {
+    budget: SearchBudget,
+    rng: ChaCha8Rng,
+    /// Rules of the game, once the game start has been seen
+    rules: GameRules,
+    seat: Option<usize>,
+    /// Card passed to the left, until the pass is seen
+    passed: Option<Card>,
+    seen: RoundKnowledge,
+    heuristic: HeuristicClient,
+}
+
+/// What the bot has seen of the round being played
+#[derive(Clone, Debug, Default)]
+struct RoundKnowledge {
+    /// Cards out of play: played in earlier tricks, turned up as trump or swapped out
+    out: Vec<Card>,
+    /// Cards in the current trick
+    trick: Vec<Card>,
+    /// Cards known to be in a player's hand, indexed by player
+    held: Vec<Vec<Card>>,
+    /// Suits a player has shown they are out of, indexed by player
+    voids: Vec<Vec<Suit>>,
+}
+
+impl RoundKnowledge {
+    fn new(num_players: usize) -> RoundKnowledge {
+        RoundKnowledge {
+            held: vec![Vec::new(); num_players],
+            voids: vec![Vec::new(); num_players],
+            ..Default::default()
+        }
+    }
+}
+
+/// Decision in the search tree
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Move {
+    ChooseTrump(Suit),
+    Bid(u8),
+    PlayCard(Card),
+    AdjustBid(i8),
+    PassCard(Card),
+}
+
+#[derive(Debug)]
+struct Node {
+    /// Move that led here and the player who made it. The root has none.
+    decision: Option<(usize, Move)>,
+    children: Vec<usize>,
+    visits: u32,
+    /// Times the node's move was legal when its parent was visited
+    availability: u32,
+    /// Total reward of the player who made the move
+    reward: f64,
+}
+
+impl Node {
+    fn new(decision: Option<(usize, Move)>) -> Node {
+        Node {
+            decision,
+            children: Vec::new(),
+            visits: 0,
+            availability: 0,
+            reward: 0.0,
+        }
+    }
+
+    fn upper_confidence_bound(&self) -> f64 {
+        let visits = self.visits as f64;
+        self.reward / visits + EXPLORATION * ((self.availability as f64).ln() / visits).sqrt()
+    }
+}
+
+/// Remove one copy of `card`, since a deck can hold several Wizards and Jesters
+fn remove_one(cards: &mut Vec<Card>, card: &Card) -> bool {
+    match cards.iter().position(|c| c == card) {
+        Some(index) => {
+            cards.swap_remove(index);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Player the engine is waiting on. Under blind bidding `me` bids first.
+fn mover(engine: &GameEngine, me: usize) -> Option<usize> {
+    match engine.turn() {
+        Turn::ChooseTrump(i)
+        | Turn::Bid(i)
+        | Turn::PlayCard(i)
+        | Turn::AdjustBid(i)
+        | Turn::PassCard(i) => Some(i),
+        Turn::BlindBid => {
+            let pending = engine.pending_bidders();
+            pending
+                .contains(&me)
+                .then_some(me)
+                .or(pending.first().copied())
+        }
+        Turn::GameOver => None,
+    }
+}
+
+fn legal_moves(engine: &GameEngine, player: usize) -> Vec<Move> {
+    match engine.turn() {
+        Turn::ChooseTrump(_) => Suit::iter().map(Move::ChooseTrump).collect(),
+        Turn::Bid(_) | Turn::BlindBid => (0..=engine.hand_size())
+            .filter(|bid| Some(*bid) != engine.forbidden_bid())
+            .map(Move::Bid)
+            .collect(),
+        Turn::PlayCard(_) => engine
+            .playable_cards(player)
+            .into_iter()
+            .flat_map(|card| match card.needs_suit() {
+                true => Suit::iter()
+                    .map(|suit| card.with_chosen_suit(suit))
+                    .collect(),
+                false => vec![card],
+            })
+            .map(Move::PlayCard)
+            .collect(),
+        Turn::AdjustBid(_) => match engine.seats()[player].bid {
+            Some(0) => vec![Move::AdjustBid(1)],
+            _ => vec![Move::AdjustBid(1), Move::AdjustBid(-1)],
+        },
+        Turn::PassCard(_) => engine
+            .hand(player)
+            .iter()
+            .map(|card| Move::PassCard(*card))
+            .collect(),
+        Turn::GameOver => Vec::new(),
+    }
+}
+
+fn apply(engine: &mut GameEngine, player: usize, decision: Move) -> Result<()> {
+    match decision {
+        Move::ChooseTrump(suit) => engine.choose_trump(player, suit),
+        Move::Bid(bid) => engine.bid(player, bid),
+        Move::PlayCard(card) => engine.play_card(player, card).map(|_| ()),
+        Move::AdjustBid(adjustment) => engine.adjust_bid(player, adjustment),
+        Move::PassCard(card) => engine.pass_card(player, card),
+    }
+}
+
+impl IsmctsClient {
+    /// Create a bot whose decisions are reproducible from `seed` when searching by iterations
+    pub fn with_seed(seed: u64) -> IsmctsClient {
+        IsmctsClient::with_rng(ChaCha8Rng::seed_from_u64(seed))
+    }
+
+    fn with_rng(rng: ChaCha8Rng) -> IsmctsClient {
+        IsmctsClient {
+            budget: SearchBudget::default(),
+            rng,
+            rules: GameRules::default(),
+            seat: None,
+            passed: None,
+            seen: RoundKnowledge::default(),
+            heuristic: HeuristicClient::new(),
+        }
+    }
+
+    /// Search for `budget` before each decision
+    pub fn set_budget(&mut self, budget: SearchBudget) {
+        self.budget = budget;
+    }
+
+    /// Deal the cards the bot can not see, consistent with what it has seen
+    ///
+    /// # Arguments
+    ///
+    /// * `hand` - Bot's own hand
+    /// * `state` - Game at the bot's bid or card
+    fn sample_hands(&mut self, me: usize, hand: &[Card], state: &GameState) -> Vec<Vec<Card>> {
+        let num_players = state.player_count;
+        let spec = self.rules.deck_for(num_players);
+        let deck_len = spec.len() + self.rules.special_cards.len();
+        let mut deck = Deck::from_spec(&mut self.rng, &spec, &self.rules.special_cards);
+        let mut unseen = deck.deal(deck_len).unwrap_or_default();
+        for card in hand.iter().chain(&state.played_cards) {
+            remove_one(&mut unseen, &card.without_chosen_suit());
+        }
+
+        // What was seen only applies when it covers every player of this round
+        let mut seen = self.seen.clone();
+        if seen.held.len() != num_players {
+            seen = RoundKnowledge::new(num_players);
+        }
+        seen.held[me].clear();
+        let mut pool = unseen.clone();
+        for card in seen.out.iter().chain(seen.held.iter().flatten()) {
+            remove_one(&mut pool, card);
+        }
+
+        // Cards still held by each player. Players who played into the trick hold one less.
+        let has_played = |player: usize| {
+            (player + num_players - state.current_leader) % num_players < state.played_cards.len()
+        };
+        let counts: Vec<usize> = (0..num_players)
+            .map(|player| hand.len() + has_played(me) as usize - has_played(player) as usize)
+            .collect();
+        let needed: usize = (0..num_players)
+            .filter(|player| *player != me)
+            .map(|player| counts[player].saturating_sub(seen.held[player].len()))
+            .sum();
+        if pool.len() < needed {
+            // What was seen does not add up, e.g. after missing events while reconnecting
+            seen = RoundKnowledge::new(num_players);
+            pool = unseen;
+        }
+        pool.shuffle(&mut self.rng);
+
+        // Deal to players with the fewest suits to choose from first
+        let mut players: Vec<usize> = (0..num_players).filter(|p| *p != me).collect();
+        players.sort_by_key(|player| std::cmp::Reverse(seen.voids[*player].len()));
+
+        let mut hands = vec![Vec::new(); num_players];
+        hands[me] = hand.to_vec();
+        for player in players {
+            let mut cards = std::mem::take(&mut seen.held[player]);
+            cards.truncate(counts[player]);
+            let voids = &seen.voids[player];
+            while cards.len() < counts[player] {
+                let follows = pool.iter().position(|card| match card {
+                    Card::NormalCard(card) => !voids.contains(&card.suit),
+                    Card::SpecialCard(_) => true,
+                });
+                match follows.or((!pool.is_empty()).then_some(0)) {
+                    Some(index) => cards.push(pool.swap_remove(index)),
+                    None => break,
+                }
+            }
+            hands[player] = cards;
+        }
+
+        hands
+    }
+
+    /// Move the search liked best, or `None` if the position could not be searched
+    fn search(&mut self, me: usize, hand: &[Card], state: &GameState) -> Option<Move> {
+        let started = Instant::now();
+        let mut nodes = vec![Node::new(None)];
+        let mut iterations = 0;
+
+        loop {
+            let done = match self.budget {
+                SearchBudget::Iterations(limit) => iterations >= limit,
+                SearchBudget::Time(limit) => iterations > 0 && started.elapsed() >= limit,
+            };
+            if done {
+                break;
+            }
+            iterations += 1;
+
+            let hands = self.sample_hands(me, hand, state);
+            let seed = self.rng.gen();
+            let game = match GameEngine::from_position(state, hands, self.rules.clone(), seed) {
+                Ok(game) => game,
+                Err(_) => return None,
+            };
+            if self.iterate(&mut nodes, game, me).is_err() {
+                return None;
+            }
+        }
+
+        nodes[0]
+            .children
+            .iter()
+            .max_by_key(|child| nodes[**child].visits)
+            .and_then(|child| nodes[*child].decision)
+            .map(|(_, decision)| decision)
+    }
+
+    /// Play out one sampled deal to the end of the round and update the tree with the result
+    fn iterate(&mut self, nodes: &mut Vec<Node>, mut game: GameEngine, me: usize) -> Result<()> {
+        let round = game.round();
+        let hand_size = game.hand_size();
+        let scores: Vec<i32> = game.seats().iter().map(|seat| seat.score).collect();
+        let in_round = |game: &GameEngine| game.round() == round && game.turn() != Turn::GameOver;
+
+        // Walk down the tree, adding the first move that has not been tried from it yet
+        let mut path = vec![0];
+        let mut node = 0;
+        while in_round(&game) {
+            let player = match mover(&game, me) {
+                Some(player) => player,
+                None => break,
+            };
+            let legal = legal_moves(&game, player);
+
+            let tried: Vec<usize> = nodes[node]
+                .children
+                .iter()
+                .copied()
+                .filter(|child| match nodes[*child].decision {
+                    Some((p, decision)) => p == player && legal.contains(&decision),
+                    None => false,
+                })
+                .collect();
+            for child in tried.iter() {
+                nodes[*child].availability += 1;
+            }
+            let untried: Vec<Move> = legal
+                .iter()
+                .filter(|decision| {
+                    !tried
+                        .iter()
+                        .any(|child| nodes[*child].decision == Some((player, **decision)))
+                })
+                .copied()
+                .collect();
+
+            if let Some(decision) = untried.choose(&mut self.rng) {
+                apply(&mut game, player, *decision)?;
+                let mut child = Node::new(Some((player, *decision)));
+                child.availability = 1;
+                nodes.push(child);
+                let child = nodes.len() - 1;
+                nodes[node].children.push(child);
+                path.push(child);
+                break;
+            }
+
+            let best = tried.iter().copied().max_by(|a, b| {
+                let a = nodes[*a].upper_confidence_bound();
+                let b = nodes[*b].upper_confidence_bound();
+                a.total_cmp(&b)
+            });
+            let best = match best {
+                Some(best) => best,
+                None => bail!("No legal move for player {}", player),
+            };
+            if let Some((_, decision)) = nodes[best].decision {
+                apply(&mut game, player, decision)?;
+            }
+            path.push(best);
+            node = best;
+        }
+
+        // Play out the rest of the round
+        while in_round(&game) {
+            let player = match mover(&game, me) {
+                Some(player) => player,
+                None => break,
+            };
+            let decision = match game.turn() {
+                Turn::Bid(_) | Turn::BlindBid => {
+                    let estimate =
+                        expected_tricks(game.hand(player), game.trump_suit(), game.num_players());
+                    Move::Bid(closest_bid(
+                        estimate,
+                        game.hand_size(),
+                        game.forbidden_bid(),
+                    ))
+                }
+                _ => match legal_moves(&game, player).choose(&mut self.rng) {
+                    Some(decision) => *decision,
+                    None => bail!("No legal move for player {}", player),
+                },
+            };
+            apply(&mut game, player, decision)?;
+        }
+
+        // Points won or lost this round, scaled to roughly one either way
+        let scale = 10.0 * (hand_size as f64 + 2.0);
+        for node in path {
+            let node = &mut nodes[node];
+            node.visits += 1;
+            if let Some((player, _)) = node.decision {
+                node.reward += (game.seats()[player].score - scores[player]) as f64 / scale;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Client for IsmctsClient {
+    /// Create a bot that searches for 1000 iterations before each decision
+    fn new() -> IsmctsClient {
+        IsmctsClient::with_rng(ChaCha8Rng::from_entropy())
+    }
+
+    fn bid(&mut self, request: &BidRequest) -> u8 {
+        let me = request.next_bidder;
+        self.seat = Some(me);
+        match self.search(me, &request.hand, &request.state) {
+            Some(Move::Bid(bid)) => bid,
+            _ => self.heuristic.bid(request),
+        }
+    }
+
+    fn choose_trump(&mut self, request: &ChooseTrumpRequest) -> Suit {
+        self.heuristic.choose_trump(request)
+    }
+
+    fn play_card(&mut self, request: &PlayCardRequest) -> Card {
+        let state = &request.state;
+        let me = (state.current_leader + state.played_cards.len()) % state.player_count.max(1);
+        self.seat = Some(me);
+        match self.search(me, &request.hand, state) {
+            Some(Move::PlayCard(card)) => card,
+            _ => self.heuristic.play_card(request),
+        }
+    }
+
+    fn adjust_bid(&mut self, request: &AdjustBidRequest) -> i8 {
+        if let Some(seat) = self.seat {
+            self.heuristic.set_seat(seat);
+        }
+        self.heuristic.adjust_bid(request)
+    }
+
+    fn pass_card(&mut self, request: &PassCardRequest) -> Card {
+        if let Some(seat) = self.seat {
+            self.heuristic.set_seat(seat);
+        }
+        let card = self.heuristic.pass_card(request);
+        self.passed = Some(card);
+        card
+    }
+
+    /// Keeps track of the cards and voids seen this round
+    fn observe(&mut self, event: &GameEvent) {
+        let seen = &mut self.seen;
+        match event {
+            GameEvent::GameStarted { rules, .. } => self.rules = rules.clone(),
+            GameEvent::CardsDealt { hands, .. } => *seen = RoundKnowledge::new(hands.len()),
+            GameEvent::TrumpRevealed {
+                card: Some(card), ..
+            } => seen.out.push(*card),
+            GameEvent::WerewolfSwapped { player, card } => {
+                remove_one(&mut seen.out, card);
+                seen.out.push(Card::SpecialCard(SpecialCard::Werewolf));
+                if let Some(held) = seen.held.get_mut(*player) {
+                    held.push(*card);
+                }
+            }
+            GameEvent::CardPlayed { player, card } => {
+                if let (Some(lead), Card::NormalCard(normal_card)) =
+                    (GameEngine::leading_suit(&seen.trick), card)
+                {
+                    if normal_card.suit != lead {
+                        if let Some(voids) = seen.voids.get_mut(*player) {
+                            voids.push(lead);
+                        }
+                    }
+                }
+                if let Some(held) = seen.held.get_mut(*player) {
+                    remove_one(held, &card.without_chosen_suit());
+                }
+                seen.trick.push(*card);
+            }
+            GameEvent::TrickWon { .. } | GameEvent::TrickBombed { .. } => {
+                let trick = std::mem::take(&mut seen.trick);
+                seen.out
+                    .extend(trick.iter().map(|card| card.without_chosen_suit()));
+            }
+            GameEvent::CardPassed { player, .. } => {
+                let num_players = seen.held.len();
+                if num_players == 0 {
+                    return;
+                }
+                // The passer may have passed a card we knew of, and the receiver may no longer be
+                // out of a suit
+                let receiver = (player + 1) % num_players;
+                seen.held[*player].clear();
+                seen.voids[receiver].clear();
+                if Some(*player) == self.seat {
+                    if let Some(card) = self.passed.take() {
+                        seen.held[receiver].push(card);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::normal_card::NormalCard;
+    use crate::cards::rank::Rank;
+    use crate::game::rules::RoundSchedule;
+    use crate::game::state::PlayerState;
+    use crate::simulation::local_game::{play_local_game, Bot};
+
+    fn card(suit: Suit, rank: Rank) -> Card {
+        Card::NormalCard(NormalCard { suit, rank })
+    }
+
+    /// Play a game between search bots. Returns the final scores.
+    fn play(seed: u64, rules: GameRules) -> Vec<i32> {
+        let mut engine = GameEngine::with_rules(3, seed, rules).unwrap();
+        let mut bots: Vec<Bot> = (0..3)
+            .map(|i| {
+                let mut client = IsmctsClient::with_seed(seed + i);
+                client.set_budget(SearchBudget::Iterations(30));
+                Box::new(client) as Bot
+            })
+            .collect();
+
+        play_local_game(&mut engine, &mut bots).unwrap();
+        engine.seats().iter().map(|seat| seat.score).collect()
+    }
+
+    #[test]
+    fn seeded_games_are_reproducible() {
+        let rules = GameRules {
+            rounds: RoundSchedule::Short { rounds: 4 },
+            special_cards: SpecialCard::iter()
+                .filter(|card| card.is_expansion())
+                .collect(),
+            ..Default::default()
+        };
+
+        let first = play(7, rules.clone());
+        assert_eq!(first, play(7, rules));
+    }
+
+    #[test]
+    fn takes_the_trick_it_needs() {
+        let hand = vec![card(Suit::Heart, Rank::Ace), card(Suit::Heart, Rank::Two)];
+        let player = |tricks_taken| PlayerState {
+            bid: Some(1),
+            tricks_taken,
+            ..Default::default()
+        };
+        let state = GameState {
+            round: 3,
+            num_rounds: 20,
+            hand_size: 3,
+            trump_suit: Some(Suit::Club),
+            player_count: 3,
+            players: vec![player(0), player(1), player(0)],
+            played_cards: vec![
+                card(Suit::Heart, Rank::King),
+                card(Suit::Heart, Rank::Queen),
+            ],
+            current_leader: 1,
+            leading_suit: Some(Suit::Heart),
+            ..Default::default()
+        };
+        let mut client = IsmctsClient::with_seed(0);
+        client.set_budget(SearchBudget::Iterations(300));
+
+        assert_eq!(
+            Some(Move::PlayCard(card(Suit::Heart, Rank::Ace))),
+            client.search(0, &hand, &state)
+        );
+    }
+}
@@ -1,3 +1,9 @@
+#[allow(clippy::module_inception)]
 pub mod client;
+pub mod config;
+pub mod heuristic_client;
 pub mod human_client;
+pub mod ismcts_client;
+pub mod process_client;
 pub mod random_client;
+pub mod spectator_client;
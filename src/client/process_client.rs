@@ -0,0 +1,445 @@
+use crate::cards::card::Card;
+use crate::cards::suit::Suit;
+use crate::client::client::Client;
+use crate::client::heuristic_client::HeuristicClient;
+use crate::game::event::GameEvent;
+use crate::network::action::{
+    AdjustBidRequest, BidRequest, ChooseTrumpRequest, PassCardRequest, PlayCardRequest,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Version of the protocol sent to the bot when it starts
+const PROTOCOL_VERSION: u32 = 1;
+/// Time the bot has to answer a request unless told otherwise
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Time the bot has to exit after being told to quit
+const QUIT_GRACE: Duration = Duration::from_millis(200);
+
+/// Client that asks a bot running as a separate program for its decisions
+///
+/// The bot reads requests from stdin and writes answers to stdout, one line each, as described
+/// in `docs/bot_protocol.md`. Whenever the bot does not answer in time or answers with something
+/// that can not be played, `HeuristicClient` decides that one move and the bot is asked again
+/// next time. Only a bot that exits or closes its stdin is given up on.
+pub struct ProcessClient {
+    /// Bot process, until it exits
+    bot: Option<BotProcess>,
+    timeout: Duration,
+    fallback: HeuristicClient,
+    seat: Option<usize>,
+}
+
+struct BotProcess {
+    child: Child,
+    stdin: ChildStdin,
+    /// Lines the bot wrote to stdout, read on a separate thread
+    lines: Receiver<String>,
+    name: String,
+    /// Id of the last request sent
+    last_request: u64,
+}
+
+impl BotProcess {
+    /// Write a command line to the bot
+    fn send(&mut self, command: &str, argument: &str) -> Result<()> {
+        writeln!(self.stdin, "{} {}", command, argument)
+            .and_then(|_| self.stdin.flush())
+            .with_context(|| format!("Failed to write to bot {}", self.name))
+    }
+
+    /// Send a request tagged with a new id. Returns the id.
+    fn request(&mut self, command: &str, argument: &str) -> Result<u64> {
+        self.last_request += 1;
+        let id = self.last_request;
+        self.send(command, &format!("{} {}", id, argument))?;
+        Ok(id)
+    }
+
+    /// The next line the bot wrote, or `None` if it wrote nothing by `deadline`
+    ///
+    /// `info` lines are logged and skipped.
+    ///
+    /// # Errors
+    /// If the bot exited
+    fn receive(&mut self, deadline: Instant) -> Result<Option<String>> {
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let line = match self.lines.recv_timeout(remaining) {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Timeout) => return Ok(None),
+                Err(RecvTimeoutError::Disconnected) => bail!("Bot {} exited", self.name),
+            };
+
+            match line.split_once(' ') {
+                Some(("info", text)) => println!("[{}] {}", self.name, text),
+                _ => return Ok(Some(line)),
+            }
+        }
+    }
+
+    /// The bot's answer to request `id` with the id left out, e.g. `bid 2`, or `None` if it did
+    /// not answer by `deadline`
+    ///
+    /// Answers to other requests, e.g. ones that came after their request timed out, are thrown
+    /// away.
+    ///
+    /// # Errors
+    /// If the bot exited
+    fn receive_answer(&mut self, id: u64, deadline: Instant) -> Result<Option<String>> {
+        while let Some(line) = self.receive(deadline)? {
+            let mut parts = line.splitn(3, ' ');
+            let (word, answer_id, answer) = (parts.next(), parts.next(), parts.next());
+            match (word, answer_id.and_then(|id| id.parse::<u64>().ok())) {
+                (Some(word), Some(answer_id)) if answer_id == id => {
+                    return Ok(Some(format!("{} {}", word, answer.unwrap_or(""))));
+                }
+                _ => eprintln!("Bot {} answered another request with {:?}", self.name, line),
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl Drop for BotProcess {
+    /// Tell the bot to quit, and kill it if it does not
+    fn drop(&mut self) {
+        let _ = self.send("quit", "");
+        let deadline = Instant::now() + QUIT_GRACE;
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = self.child.try_wait() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Parse a card, suit or number written the way it is in JSON, e.g. `AH` or `Heart`
+fn parse_word<T: DeserializeOwned>(word: &str) -> Result<T> {
+    serde_json::from_value(serde_json::Value::String(word.to_string()))
+        .or_else(|_| serde_json::from_str(word))
+        .map_err(|_| anyhow!("Can not read {:?}", word))
+}
+
+impl ProcessClient {
+    /// Start `program` with `args` and wait for it to say it is ready
+    ///
+    /// # Errors
+    /// If the program can not be started or does not answer `wizard` with `ready` within
+    /// `timeout`
+    pub fn spawn(program: &str, args: &[String], timeout: Duration) -> Result<ProcessClient> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .with_context(|| format!("Failed to start bot {}", program))?;
+
+        let stdin = child.stdin.take().context("Bot has no stdin")?;
+        let stdout = child.stdout.take().context("Bot has no stdout")?;
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    return;
+                }
+            }
+        });
+
+        let mut bot = BotProcess {
+            child,
+            stdin,
+            lines,
+            name: program.to_string(),
+            last_request: 0,
+        };
+        bot.send("wizard", &PROTOCOL_VERSION.to_string())?;
+        match bot.receive(Instant::now() + timeout)? {
+            Some(line) if line.split_whitespace().next() == Some("ready") => {
+                let name = line["ready".len()..].trim();
+                if !name.is_empty() {
+                    bot.name = name.to_string();
+                }
+            }
+            Some(line) => bail!("Bot {} answered wizard with {:?}", program, line),
+            None => bail!("Bot {} did not say it is ready in time", program),
+        }
+
+        Ok(ProcessClient {
+            bot: Some(bot),
+            timeout,
+            fallback: HeuristicClient::new(),
+            seat: None,
+        })
+    }
+
+    /// Time the bot has to answer each request
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Send a request to the bot and read its answer
+    ///
+    /// `None` if the bot did not answer in time, gave an answer that can not be read, or exited.
+    fn ask<T: DeserializeOwned>(&mut self, command: &str, request: &impl Serialize) -> Option<T> {
+        let bot = self.bot.as_mut()?;
+        let deadline = Instant::now() + self.timeout;
+        let line = serde_json::to_string(request)
+            .map_err(Into::into)
+            .and_then(|request| bot.request(command, &request))
+            .and_then(|id| bot.receive_answer(id, deadline));
+
+        let line = match line {
+            Ok(Some(line)) => line,
+            Ok(None) => {
+                eprintln!("Bot {} did not answer {} in time", bot.name, command);
+                return None;
+            }
+            Err(e) => {
+                eprintln!("{:#}. Playing on without it.", e);
+                self.bot = None;
+                return None;
+            }
+        };
+
+        let answer = match line.split_once(' ') {
+            Some((word, answer)) if word == command => parse_word(answer.trim()),
+            _ => Err(anyhow!("Expected a {} answer", command)),
+        };
+        match answer {
+            Ok(answer) => Some(answer),
+            Err(e) => {
+                eprintln!(
+                    "Bot {} answered {} with {:?}. {:#}",
+                    bot.name, command, line, e
+                );
+                None
+            }
+        }
+    }
+
+    /// Let the fallback decide for the seat the bot was playing
+    fn fallback(&mut self) -> &mut HeuristicClient {
+        if let Some(seat) = self.seat {
+            self.fallback.set_seat(seat);
+        }
+        &mut self.fallback
+    }
+
+    /// Whether the bot process is still playing
+    pub fn is_running(&self) -> bool {
+        self.bot.is_some()
+    }
+}
+
+impl Client for ProcessClient {
+    /// Create a client without a bot process, so the fallback makes every decision
+    fn new() -> ProcessClient {
+        ProcessClient {
+            bot: None,
+            timeout: DEFAULT_TIMEOUT,
+            fallback: HeuristicClient::new(),
+            seat: None,
+        }
+    }
+
+    fn bid(&mut self, request: &BidRequest) -> u8 {
+        self.seat = Some(request.next_bidder);
+        match self.ask::<u8>("bid", request) {
+            Some(bid) if bid <= request.state.hand_size && Some(bid) != request.forbidden_bid => {
+                bid
+            }
+            answer => {
+                if answer.is_some() {
+                    eprintln!("Bot bid {:?} is not allowed", answer);
+                }
+                self.fallback().bid(request)
+            }
+        }
+    }
+
+    fn choose_trump(&mut self, request: &ChooseTrumpRequest) -> Suit {
+        match self.ask("trump", request) {
+            Some(suit) => suit,
+            None => self.fallback().choose_trump(request),
+        }
+    }
+
+    fn play_card(&mut self, request: &PlayCardRequest) -> Card {
+        let state = &request.state;
+        self.seat = Some((state.current_leader + state.played_cards.len()) % state.player_count);
+        match self.ask::<Card>("play", request) {
+            Some(card)
+                if !card.needs_suit()
+                    && request.playable_cards.contains(&card.without_chosen_suit()) =>
+            {
+                card
+            }
+            answer => {
+                if let Some(card) = answer {
+                    eprintln!("Bot played {:?} which can not be played", card);
+                }
+                self.fallback().play_card(request)
+            }
+        }
+    }
+
+    fn adjust_bid(&mut self, request: &AdjustBidRequest) -> i8 {
+        match self.ask::<i8>("adjust", request) {
+            Some(1) => 1,
+            Some(-1) if request.bid > 0 => -1,
+            _ => self.fallback().adjust_bid(request),
+        }
+    }
+
+    fn pass_card(&mut self, request: &PassCardRequest) -> Card {
+        match self.ask::<Card>("pass", request) {
+            Some(card) if request.hand.contains(&card) => card,
+            _ => self.fallback().pass_card(request),
+        }
+    }
+
+    /// Forwards the event to the bot without waiting for an answer
+    fn observe(&mut self, event: &GameEvent) {
+        self.fallback.observe(event);
+        if let Some(bot) = self.bot.as_mut() {
+            let sent = serde_json::to_string(event)
+                .map_err(Into::into)
+                .and_then(|event| bot.send("event", &event));
+            if let Err(e) = sent {
+                eprintln!("{:#}. Playing on without it.", e);
+                self.bot = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::engine::{GameEngine, Turn};
+    use crate::game::rules::{GameRules, RoundSchedule};
+    use crate::simulation::local_game::{play_local_game, Bot};
+
+    /// Bot that bids 0 and plays the first playable card, written as a shell script
+    const FIRST_CARD_BOT: &str = r#"
+        while read -r command argument; do
+            case "$command" in
+                wizard) echo "ready first-card" ;;
+                bid) echo "info bidding"; echo "bid ${argument%% *} 0" ;;
+                trump) echo "trump ${argument%% *} Spade" ;;
+                play)
+                    card=$(echo "$argument" | sed 's/.*"playable_cards":\["\([^"]*\)".*/\1/')
+                    echo "play ${argument%% *} $card" ;;
+                quit) exit 0 ;;
+            esac
+        done
+    "#;
+
+    fn spawn_script(script: &str, timeout: Duration) -> Result<ProcessClient> {
+        ProcessClient::spawn("sh", &["-c".to_string(), script.to_string()], timeout)
+    }
+
+    /// Play a game with `client` in seat 0 and heuristic bots in the other seats
+    fn play_with(client: ProcessClient, rules: GameRules) -> GameEngine {
+        let mut engine = GameEngine::with_rules(3, 8, rules).unwrap();
+        let mut bots: Vec<Bot> = vec![
+            Box::new(client),
+            Box::new(HeuristicClient::new()),
+            Box::new(HeuristicClient::new()),
+        ];
+        play_local_game(&mut engine, &mut bots).unwrap();
+        engine
+    }
+
+    #[test]
+    fn bot_process_plays_a_game() {
+        let client = spawn_script(FIRST_CARD_BOT, DEFAULT_TIMEOUT).unwrap();
+        assert!(client.is_running());
+
+        let engine = play_with(client, GameRules::default());
+        assert_eq!(Turn::GameOver, engine.turn());
+        // The script bids 0 every round
+        let bids = engine
+            .events()
+            .iter()
+            .filter(|event| matches!(event, GameEvent::BidPlaced { player: 0, bid } if *bid != 0));
+        assert_eq!(0, bids.count());
+    }
+
+    #[test]
+    fn silent_bot_is_replaced_by_the_fallback() {
+        let silent = "read -r line; echo 'ready silent'; exec sleep 30";
+        let mut client = spawn_script(silent, Duration::from_secs(5)).unwrap();
+        client.set_timeout(Duration::from_millis(100));
+
+        let started = Instant::now();
+        let rules = GameRules {
+            rounds: RoundSchedule::Short { rounds: 3 },
+            ..Default::default()
+        };
+        let engine = play_with(client, rules);
+        assert_eq!(Turn::GameOver, engine.turn());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn bot_is_asked_again_after_a_late_or_bad_answer() {
+        // Skips the first bid, answering it late along with the second. Answers the third with
+        // nonsense. Otherwise bids as many tricks as it has cards, which is the round number.
+        let script = r#"
+            bids=0
+            while read -r command argument; do
+                id=${argument%% *}
+                case "$command" in
+                    wizard) echo "ready slow-start" ;;
+                    bid)
+                        bids=$((bids + 1))
+                        if [ "$bids" = 1 ]; then skipped=$id
+                        elif [ "$bids" = 2 ]; then echo "bid $skipped 0"; echo "bid $id 2"
+                        elif [ "$bids" = 3 ]; then echo "bid $id many"
+                        else echo "bid $id $bids"; fi ;;
+                    trump) echo "trump $id Spade" ;;
+                    play)
+                        card=$(echo "$argument" | sed 's/.*"playable_cards":\["\([^"]*\)".*/\1/')
+                        echo "play $id $card" ;;
+                    quit) exit 0 ;;
+                esac
+            done
+        "#;
+        let mut client = spawn_script(script, DEFAULT_TIMEOUT).unwrap();
+        client.set_timeout(Duration::from_millis(500));
+
+        let engine = play_with(client, GameRules::default());
+        assert_eq!(Turn::GameOver, engine.turn());
+        // The fallback bids the first and third rounds. Had the late answer been taken as the
+        // answer to the second request, or the bot been given up on, the bids would differ.
+        let bids: Vec<u8> = engine
+            .events()
+            .iter()
+            .filter_map(|event| match event {
+                GameEvent::BidPlaced { player: 0, bid } => Some(*bid),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(20, bids.len());
+        assert_eq!(2, bids[1]);
+        assert_eq!((4..=20).collect::<Vec<u8>>(), bids[3..]);
+    }
+
+    #[test]
+    fn bot_must_say_it_is_ready() {
+        assert!(spawn_script("read -r line; echo 'hello'", DEFAULT_TIMEOUT).is_err());
+        assert!(ProcessClient::spawn("/nonexistent/bot", &[], DEFAULT_TIMEOUT).is_err());
+    }
+}
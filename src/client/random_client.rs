@@ -1,47 +1,74 @@
 use crate::cards::card::Card;
 use crate::cards::suit::Suit;
 use crate::client::client::Client;
-use crate::network::action::Action;
-use crate::network::network::{network_listener, network_writer, wait_for_incoming_connection};
-use local_ip_address::local_ip;
-use rand::Rng;
-use serde_json::{json, Value};
-use std::net::{TcpListener, TcpStream};
+use crate::network::action::{
+    AdjustBidRequest, BidRequest, ChooseTrumpRequest, PassCardRequest, PlayCardRequest,
+};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 use strum::IntoEnumIterator;
 
 pub struct RandomClient {
-    rng: rand::rngs::ThreadRng,
+    rng: ChaCha8Rng,
+}
+
+impl RandomClient {
+    fn random_suit(&mut self) -> Suit {
+        let suits: Vec<Suit> = Suit::iter().collect();
+        let index = self.rng.gen_range(0..suits.len());
+        suits[index]
+    }
+
+    /// Create a random client whose decisions are reproducible from `seed`
+    pub fn with_seed(seed: u64) -> RandomClient {
+        RandomClient {
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
 }
 
 impl Client for RandomClient {
     /// Create a new random client
     fn new() -> RandomClient {
-        let rng = rand::thread_rng();
-
-        RandomClient { rng }
+        RandomClient {
+            rng: ChaCha8Rng::from_entropy(),
+        }
     }
 
-    /// Generates a random bid between 0 and the round number
-    fn bid(&mut self, json: &Value) -> u8 {
-        // Get round number
-        let num_players = json["state"]["round"].as_u64().unwrap();
-        // Make random bend
-        self.rng.gen_range(0..=num_players) as u8
+    /// Generates a random bid between 0 and the round number, avoiding the forbidden bid
+    fn bid(&mut self, request: &BidRequest) -> u8 {
+        loop {
+            let bid = self.rng.gen_range(0..=request.state.hand_size);
+            if Some(bid) != request.forbidden_bid {
+                return bid;
+            }
+        }
     }
 
     /// Picks a random trump suit
-    fn choose_trump(&mut self, _: &Value) -> Suit {
-        let suits: Vec<Suit> = Suit::iter().collect();
-        let index = self.rng.gen_range(0..suits.len());
-        suits[index]
+    fn choose_trump(&mut self, _: &ChooseTrumpRequest) -> Suit {
+        self.random_suit()
+    }
+
+    /// Picks a random card from "playable_cards", playing a Juggler or Cloud as a random suit
+    fn play_card(&mut self, request: &PlayCardRequest) -> Card {
+        let index = self.rng.gen_range(0..request.playable_cards.len());
+        let suit = self.random_suit();
+        request.playable_cards[index].with_chosen_suit(suit)
+    }
+
+    /// Raises the bid if it is zero, otherwise raises or lowers it at random
+    fn adjust_bid(&mut self, request: &AdjustBidRequest) -> i8 {
+        if request.bid == 0 || self.rng.gen() {
+            1
+        } else {
+            -1
+        }
     }
 
-    /// Picks a random card from "playable_cards"
-    fn play_card(&mut self, json: &Value) -> Card {
-        // Get playable cards
-        let playable_cards: Vec<Card> =
-            serde_json::from_value(json["playable_cards"].clone()).unwrap();
-        let index = self.rng.gen_range(0..playable_cards.len());
-        playable_cards[index]
+    /// Picks a random card from the hand
+    fn pass_card(&mut self, request: &PassCardRequest) -> Card {
+        let index = self.rng.gen_range(0..request.hand.len());
+        request.hand[index]
     }
 }
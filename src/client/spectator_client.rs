@@ -0,0 +1,50 @@
+use crate::game::event::GameEvent;
+use crate::network::action::{Action, ClientAction};
+use crate::network::connection::Connection;
+use anyhow::{bail, Result};
+
+/// Watch a game without a seat and print what happens
+///
+/// Returns every event seen once the game ends. The events of an omniscient spectator, who gave
+/// the server's admin token, can be written with `write_event_log` and replayed.
+///
+/// # Arguments
+///
+/// * `table` - Lobby table to watch. Not needed for a server hosting a single game.
+/// * `admin_token` - Server's admin token, to see every hand
+pub fn spectate(
+    host: &str,
+    port: &str,
+    name: &str,
+    table: Option<u64>,
+    admin_token: Option<String>,
+) -> Result<Vec<GameEvent>> {
+    let mut server = Connection::connect(host, port)?;
+    server.send(&ClientAction::Spectate {
+        name: name.to_string(),
+        table,
+        admin_token,
+    });
+
+    let mut events = Vec::new();
+    loop {
+        match server.receive::<Action>()? {
+            Action::Confirmation(confirmation) => println!("{}", confirmation.msg),
+            Action::Error(error) => bail!("Server refused ({:?}): {}", error.reason, error.msg),
+            Action::Event(message) => {
+                println!("{:?}", message.event);
+                if let Some(hands) = &message.hands {
+                    println!("Hands: {}", serde_json::to_string(hands)?);
+                }
+                events.push(message.event);
+            }
+            Action::EndGame(end_game) => {
+                println!("Game has ended. Final Game State: {:#?}", end_game.state);
+                break;
+            }
+            action => eprintln!("Ignoring unexpected message: {:?}", action),
+        }
+    }
+
+    Ok(events)
+}
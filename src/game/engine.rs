@@ -0,0 +1,2332 @@
+use crate::cards::card::Card;
+use crate::cards::deck::Deck;
+use crate::cards::rank::Rank;
+use crate::cards::special_card::SpecialCard;
+use crate::cards::suit::Suit;
+use crate::game::event::GameEvent;
+use crate::game::rule_violation::RuleViolation;
+use crate::game::rules::GameRules;
+use crate::game::scoring::{RoundResult, ScoringRule};
+use crate::game::state::{GameState, PlacedBid, PlayerState};
+use anyhow::{anyhow, bail, Context, Result};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::sync::Arc;
+
+/// Most players a game can seat, whatever the deck
+///
+/// The extended deck for large tables has 8 full rounds at 8 players. Larger tables would have
+/// too few rounds for a real game.
+pub const MAX_PLAYERS: usize = 8;
+
+/// Input the engine is waiting on before it can advance
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Turn {
+    /// Player must choose the trump suit since a Wizard was revealed as trump or they swapped in
+    /// the Werewolf
+    ChooseTrump(usize),
+    /// Player must bid for this round
+    Bid(usize),
+    /// Every player in `pending_bidders` must bid without seeing the other bids
+    BlindBid,
+    /// Player must play a card into the current trick
+    PlayCard(usize),
+    /// Player won a trick with a Cloud in it and must raise or lower their bid by one
+    AdjustBid(usize),
+    /// Player must pass a card to the player on their left since a Juggler was played
+    PassCard(usize),
+    /// All rounds have been played
+    GameOver,
+}
+
+/// Per player state tracked by the engine
+#[derive(Clone, Debug, Default)]
+pub struct Seat {
+    pub name: String,
+    pub score: i32,
+    pub bid: Option<u8>,
+    pub cards: Vec<Card>,
+    pub tricks_taken: u8,
+}
+
+impl Seat {
+    pub fn won_trick(&mut self) {
+        self.tricks_taken += 1;
+    }
+
+    /// Update the score and reset bid and tricks_taken
+    /// # Panics
+    /// If a player has cards remaining or if the player does not have a bid yet.
+    pub fn update_score(&mut self, scoring: &dyn ScoringRule, round: u8, num_rounds: u8) {
+        match self.bid {
+            Some(bid) => {
+                // Custom rules can score anything, so the total saturates rather than wraps
+                self.score = self.score.saturating_add(scoring.score(&RoundResult {
+                    round,
+                    num_rounds,
+                    bid,
+                    tricks_taken: self.tricks_taken,
+                }));
+            }
+            None => panic!("Cannot update score before having a bid!"),
+        }
+        self.bid = None;
+        self.tricks_taken = 0;
+        if !self.cards.is_empty() {
+            panic!("Cannot calculate score before all cards have been played!");
+        }
+    }
+}
+
+/// Rules engine for a game of Wizard
+///
+/// The engine does not know anything about how players are connected. It advances through
+/// dealing, revealing trump, bidding and playing tricks purely by accepting inputs through
+/// `choose_trump`, `bid`, `play_card`, `adjust_bid` and `pass_card`. `turn` tells the caller
+/// which input is expected next.
+#[derive(Clone, Debug)]
+pub struct GameEngine {
+    seats: Vec<Seat>,
+    rules: GameRules,
+    scoring: Arc<dyn ScoringRule>,
+    /// Player dealing this round
+    dealer: usize,
+    /// Player who leads the current trick
+    current_leader: usize,
+    round: u8,
+    num_rounds: u8,
+    /// Cards dealt to each player in each round
+    hand_sizes: Vec<u8>,
+    trump_suit: Option<Suit>,
+    played_cards: Vec<Card>,
+    /// Bids made so far under blind bidding, kept hidden until every player has bid
+    blind_bids: Vec<Option<u8>>,
+    /// Player who must adjust their bid after winning a trick with a Cloud
+    bid_adjustment: Option<usize>,
+    /// Cards passed to the left after a Juggler was played, indexed by the passing player
+    passed_cards: Option<Vec<Option<Card>>>,
+    turn: Turn,
+    seed: u64,
+    rng: ChaCha8Rng,
+    /// Everything that has happened so far, in order
+    events: Vec<GameEvent>,
+}
+
+impl GameEngine {
+    /// Create a new game with a random seed and deal the first round
+    pub fn new(num_players: usize) -> Result<GameEngine> {
+        GameEngine::with_seed(num_players, rand::random())
+    }
+
+    /// Create a new game with the standard rules and deal the first round
+    ///
+    /// Every deal is drawn from an rng seeded with `seed`, so the same seed and the same player
+    /// decisions always produce an identical game.
+    pub fn with_seed(num_players: usize, seed: u64) -> Result<GameEngine> {
+        GameEngine::with_rules(num_players, seed, GameRules::default())
+    }
+
+    /// Create a new game played with `rules` and deal the first round
+    pub fn with_rules(num_players: usize, seed: u64, mut rules: GameRules) -> Result<GameEngine> {
+        if num_players < 2 {
+            bail!(
+                "Not enough players. Minimum of 2 players required. Players requested: {}",
+                num_players
+            );
+        }
+        if num_players > MAX_PLAYERS {
+            bail!(
+                "Too many players. Maximum of {} players allowed. Players requested: {}",
+                MAX_PLAYERS,
+                num_players
+            );
+        }
+
+        if rules.restricted_bidding && rules.blind_bidding {
+            bail!(
+                "Restricted bidding needs a last bidder so it cannot be played with blind bidding"
+            );
+        }
+
+        for (i, special_card) in rules.special_cards.iter().enumerate() {
+            let card = Card::SpecialCard(*special_card);
+            if !special_card.is_expansion()
+                || card != card.without_chosen_suit()
+                || rules.special_cards[..i].contains(special_card)
+            {
+                bail!(
+                    "Special cards must be distinct anniversary edition cards. Card: {}",
+                    special_card
+                );
+            }
+        }
+
+        // Recorded in the rules so clients and replays see the deck that was played
+        let deck = rules.deck_for(num_players);
+        rules.deck = Some(deck.clone());
+        for (i, suit) in deck.suits.iter().enumerate() {
+            if deck.suits[..i].contains(suit) {
+                bail!("Deck suits must be distinct. Suit listed twice: {:?}", suit);
+            }
+        }
+        for (i, rank) in deck.removed_ranks.iter().enumerate() {
+            if deck.removed_ranks[..i].contains(rank) {
+                bail!(
+                    "Removed ranks must be distinct. Rank listed twice: {:?}",
+                    rank
+                );
+            }
+        }
+        for (i, rank) in deck.extra_ranks.iter().enumerate() {
+            if !Rank::EXTRA.contains(rank) || deck.extra_ranks[..i].contains(rank) {
+                bail!(
+                    "Extra ranks must be distinct and one of {:?}. Rank: {:?}",
+                    Rank::EXTRA,
+                    rank
+                );
+            }
+        }
+
+        // The deck decides how many players fit at the table
+        let deck_len = deck.len() + rules.special_cards.len();
+        if deck_len < num_players {
+            bail!(
+                "Too many players. Deck of {} cards is too small for {} players",
+                deck_len,
+                num_players
+            );
+        }
+        let max_hand_size = u8::try_from(deck_len / num_players).unwrap_or(u8::MAX);
+        let hand_sizes = rules.rounds.hand_sizes(max_hand_size)?;
+        let num_rounds = u8::try_from(hand_sizes.len()).map_err(|_| {
+            anyhow!(
+                "Too many rounds. At most {} rounds can be played. Rounds in schedule: {}",
+                u8::MAX,
+                hand_sizes.len()
+            )
+        })?;
+
+        let mut engine = GameEngine {
+            seats: vec![Seat::default(); num_players],
+            scoring: rules.scoring.rule()?,
+            rules: rules.clone(),
+            dealer: 0,
+            current_leader: 0,
+            round: 0,
+            num_rounds,
+            hand_sizes,
+            trump_suit: None,
+            played_cards: Vec::new(),
+            blind_bids: Vec::new(),
+            bid_adjustment: None,
+            passed_cards: None,
+            turn: Turn::GameOver,
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            events: vec![GameEvent::GameStarted {
+                num_players,
+                seed: Some(seed),
+                rules,
+            }],
+        };
+        engine.start_round()?;
+
+        Ok(engine)
+    }
+
+    /// Rebuild a game from its events
+    ///
+    /// Deals are reproduced from the seed in `GameStarted` and player inputs are applied in
+    /// order, so any prefix of a game's events rebuilds the game as it was at that point.
+    ///
+    /// # Errors
+    /// If the events do not start with `GameStarted`, the events were redacted, an input breaks
+    /// the rules, or the replayed game does not produce the same events
+    pub fn replay(events: &[GameEvent]) -> Result<GameEngine> {
+        let mut engine = match events.first() {
+            Some(GameEvent::GameStarted {
+                num_players,
+                seed: Some(seed),
+                rules,
+            }) => GameEngine::with_rules(*num_players, *seed, rules.clone())?,
+            Some(GameEvent::GameStarted { seed: None, .. }) => {
+                bail!("Redacted event logs can not be replayed")
+            }
+            _ => bail!("Event log must start with a GameStarted event"),
+        };
+
+        for (i, event) in events.iter().enumerate() {
+            match *event {
+                GameEvent::TrumpChosen { player, trump } => engine.choose_trump(player, trump),
+                GameEvent::BidPlaced { player, bid } => engine.bid(player, bid),
+                GameEvent::CardPlayed { player, card } => {
+                    engine.play_card(player, card).map(|_| ())
+                }
+                GameEvent::BidAdjusted { player, adjustment } => {
+                    engine.adjust_bid(player, adjustment)
+                }
+                GameEvent::CardPassed {
+                    player,
+                    card: Some(card),
+                } => engine.pass_card(player, card),
+                GameEvent::CardPassed { card: None, .. } => {
+                    bail!("Redacted event logs can not be replayed")
+                }
+                // Everything else follows from the seed and the player inputs
+                _ => Ok(()),
+            }
+            .with_context(|| format!("Failed to replay event {}: {:?}", i, event))?;
+        }
+
+        if !engine.events.starts_with(events) {
+            bail!("Event log does not match the replayed game");
+        }
+
+        Ok(engine)
+    }
+
+    /// A game picked up at the bid or card `state` is waiting on, with `hands` as every player's
+    /// cards
+    ///
+    /// Lets bots play out what might happen from a position where they only know their own hand.
+    /// The position has no events, so it can not be replayed. Later rounds are dealt from `seed`.
+    ///
+    /// # Errors
+    /// If `rules` can not be played by this many players, `state` is not from a game played with
+    /// them, or `hands` do not fit the trick in progress
+    pub fn from_position(
+        state: &GameState,
+        hands: Vec<Vec<Card>>,
+        rules: GameRules,
+        seed: u64,
+    ) -> Result<GameEngine> {
+        let num_players = state.player_count;
+        let mut engine = GameEngine::with_rules(num_players, seed, rules)?;
+        if state.round == 0
+            || state.round > engine.num_rounds
+            || engine.hand_sizes[state.round as usize - 1] != state.hand_size
+            || state.players.len() != num_players
+            || hands.len() != num_players
+        {
+            bail!("Game state does not fit a game played with these rules");
+        }
+
+        engine.events.clear();
+        engine.round = state.round;
+        engine.dealer = state.dealer;
+        engine.current_leader = state.current_leader;
+        engine.trump_suit = state.trump_suit;
+        engine.played_cards = state.played_cards.clone();
+        for ((seat, player), cards) in engine.seats.iter_mut().zip(&state.players).zip(hands) {
+            seat.name = player.name.clone();
+            seat.score = player.score;
+            seat.bid = player.bid;
+            seat.tricks_taken = player.tricks_taken;
+            seat.cards = cards;
+        }
+
+        let bids = state.players.iter().filter(|player| player.bid.is_some());
+        engine.turn = if bids.count() == num_players {
+            let player = (state.current_leader + state.played_cards.len()) % num_players;
+            Turn::PlayCard(player)
+        } else if !state.played_cards.is_empty() {
+            bail!("Cards were played before every player bid");
+        } else {
+            engine.start_bidding();
+            match engine.turn {
+                Turn::Bid(first) => Turn::Bid((first + engine.bids().len()) % num_players),
+                turn => turn,
+            }
+        };
+
+        // Players who already played into the trick hold one card less
+        let held_before_trick =
+            |i: usize| engine.seats[i].cards.len() + engine.has_played(i) as usize;
+        if (0..num_players).any(|i| held_before_trick(i) != held_before_trick(0)) {
+            bail!("Hands do not fit the trick in progress");
+        }
+
+        Ok(engine)
+    }
+
+    /// Whether a player already played into the current trick
+    fn has_played(&self, player: usize) -> bool {
+        let num_players = self.seats.len();
+        (player + num_players - self.current_leader) % num_players < self.played_cards.len()
+    }
+
+    /// Everything that has happened so far, in order
+    pub fn events(&self) -> &[GameEvent] {
+        &self.events
+    }
+
+    /// Optional rules this game is played with
+    pub fn rules(&self) -> &GameRules {
+        &self.rules
+    }
+
+    /// Seed used to shuffle every deck of this game
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Input the engine is currently waiting on
+    pub fn turn(&self) -> Turn {
+        self.turn
+    }
+
+    pub fn num_players(&self) -> usize {
+        self.seats.len()
+    }
+
+    pub fn round(&self) -> u8 {
+        self.round
+    }
+
+    pub fn num_rounds(&self) -> u8 {
+        self.num_rounds
+    }
+
+    pub fn trump_suit(&self) -> Option<Suit> {
+        self.trump_suit
+    }
+
+    /// Cards played so far in the current trick
+    pub fn played_cards(&self) -> &[Card] {
+        &self.played_cards
+    }
+
+    pub fn seats(&self) -> &[Seat] {
+        &self.seats
+    }
+
+    pub fn set_name(&mut self, player: usize, name: &str) {
+        self.seats[player].name = name.to_string();
+    }
+
+    /// Cards in a player's hand
+    pub fn hand(&self, player: usize) -> &[Card] {
+        &self.seats[player].cards
+    }
+
+    /// Cards dealt to each player this round
+    pub fn hand_size(&self) -> u8 {
+        self.hand_sizes[self.round as usize - 1]
+    }
+
+    /// Player that deals this round. They choose trump when a Wizard is revealed.
+    pub fn dealer(&self) -> usize {
+        self.dealer
+    }
+
+    /// Player who leads the current trick
+    pub fn current_leader(&self) -> usize {
+        self.current_leader
+    }
+
+    /// Player to the left of the dealer. They bid first and lead the first trick of the round.
+    fn first_player(&self) -> usize {
+        (self.dealer + 1) % self.seats.len()
+    }
+
+    /// Deal a new round and reveal trump
+    ///
+    /// The deal moves one seat to the left every round
+    fn start_round(&mut self) -> Result<()> {
+        self.round += 1;
+        self.dealer = (self.round as usize - 1) % self.seats.len();
+        self.played_cards.clear();
+        self.current_leader = self.first_player();
+
+        let spec = self.rules.deck_for(self.seats.len());
+        let mut deck = Deck::from_spec(&mut self.rng, &spec, &self.rules.special_cards);
+        // Deal the cards and set the trump suit
+        self.deal(&mut deck)?;
+        // Reveal trump - Allow player to choose if trump is a wizard
+        self.reveal_trump(&mut deck);
+
+        Ok(())
+    }
+
+    /// Deal the shuffled deck of cards to each player based on the round number
+    fn deal(&mut self, deck: &mut Deck) -> Result<()> {
+        let hand_size = self.hand_size() as usize;
+        for seat in self.seats.iter_mut() {
+            seat.cards = deck.deal(hand_size)?;
+        }
+        self.events.push(GameEvent::CardsDealt {
+            round: self.round,
+            dealer: self.dealer,
+            hands: self.seats.iter().map(|seat| seat.cards.clone()).collect(),
+        });
+
+        Ok(())
+    }
+
+    /// Reveal trump. If the trump is a wizard, the dealer gets to choose the trump suit
+    ///
+    /// A player dealt the Werewolf swaps it for the trump card and chooses the trump suit instead.
+    fn reveal_trump(&mut self, deck: &mut Deck) {
+        self.trump_suit = None;
+        self.start_bidding();
+
+        let card = deck.deal(1).ok().map(|cards| cards[0]);
+        match card {
+            Some(Card::SpecialCard(SpecialCard::Wizard)) => {
+                self.turn = Turn::ChooseTrump(self.dealer);
+            }
+            Some(Card::NormalCard(normal_card)) => {
+                self.trump_suit = Some(normal_card.suit);
+            }
+            // No other special card has a suit
+            Some(Card::SpecialCard(_)) | None => {}
+        }
+        self.events.push(GameEvent::TrumpRevealed {
+            card,
+            trump_suit: self.trump_suit,
+        });
+
+        // Without a trump card to swap with, the Werewolf stays in hand and plays like a Jester
+        let werewolf = Card::SpecialCard(SpecialCard::Werewolf);
+        let holder = self
+            .seats
+            .iter()
+            .position(|seat| seat.cards.contains(&werewolf));
+        if let (Some(player), Some(card)) = (holder, card) {
+            let hand = &mut self.seats[player].cards;
+            hand.retain(|c| *c != werewolf);
+            hand.push(card);
+            self.trump_suit = None;
+            self.turn = Turn::ChooseTrump(player);
+            self.events
+                .push(GameEvent::WerewolfSwapped { player, card });
+        }
+    }
+
+    /// Set the trump suit after a Wizard was revealed as trump or the Werewolf was swapped in
+    pub fn choose_trump(&mut self, player: usize, suit: Suit) -> Result<()> {
+        if self.turn != Turn::ChooseTrump(player) {
+            bail!(RuleViolation::NotYourTurn { player });
+        }
+
+        self.trump_suit = Some(suit);
+        self.events.push(GameEvent::TrumpChosen {
+            player,
+            trump: suit,
+        });
+        self.start_bidding();
+
+        Ok(())
+    }
+
+    /// Ask for bids starting with the player to the left of the dealer, or from everyone at once
+    /// under blind bidding
+    fn start_bidding(&mut self) {
+        if self.rules.blind_bidding {
+            self.blind_bids = vec![None; self.seats.len()];
+            self.turn = Turn::BlindBid;
+        } else {
+            self.turn = Turn::Bid(self.first_player());
+        }
+    }
+
+    /// Players who still have to bid this round
+    pub fn pending_bidders(&self) -> Vec<usize> {
+        match self.turn {
+            Turn::Bid(player) => vec![player],
+            Turn::BlindBid => (0..self.seats.len())
+                .filter(|player| self.blind_bids[*player].is_none())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Bids made so far this round, in the order they were made
+    pub fn bids(&self) -> Vec<PlacedBid> {
+        let num_players = self.seats.len();
+        (0..num_players)
+            .map(|i| (self.first_player() + i) % num_players)
+            .map_while(|player| self.seats[player].bid.map(|bid| PlacedBid { player, bid }))
+            .collect()
+    }
+
+    /// Bid the current bidder is not allowed to make
+    ///
+    /// Only the last bidder of a round has a forbidden bid, and only under restricted bidding.
+    pub fn forbidden_bid(&self) -> Option<u8> {
+        let player = match self.turn {
+            Turn::Bid(player) => player,
+            _ => return None,
+        };
+        if !self.rules.restricted_bidding || player != self.dealer {
+            return None;
+        }
+
+        let total: u8 = self.bids().iter().map(|placed| placed.bid).sum();
+        self.hand_size().checked_sub(total)
+    }
+
+    /// Record a player's bid for this round
+    ///
+    /// Under blind bidding players bid in any order, and the bids are revealed together once
+    /// every player has bid.
+    pub fn bid(&mut self, player: usize, bid: u8) -> Result<()> {
+        if !self.pending_bidders().contains(&player) {
+            bail!(RuleViolation::NotYourTurn { player });
+        }
+
+        if bid > self.hand_size() {
+            bail!(RuleViolation::InvalidBid {
+                bid,
+                hand_size: self.hand_size(),
+            });
+        }
+
+        if self.forbidden_bid() == Some(bid) {
+            bail!(RuleViolation::ForbiddenBid { bid });
+        }
+
+        if self.turn == Turn::BlindBid {
+            self.blind_bids[player] = Some(bid);
+            if self.pending_bidders().is_empty() {
+                self.reveal_blind_bids();
+            }
+            return Ok(());
+        }
+
+        self.seats[player].bid = Some(bid);
+        self.events.push(GameEvent::BidPlaced { player, bid });
+
+        let next_player = (player + 1) % self.seats.len();
+        self.turn = if next_player == self.first_player() {
+            Turn::PlayCard(self.current_leader)
+        } else {
+            Turn::Bid(next_player)
+        };
+
+        Ok(())
+    }
+
+    /// Reveal every blind bid in bidding order and start the first trick
+    fn reveal_blind_bids(&mut self) {
+        let num_players = self.seats.len();
+        for i in 0..num_players {
+            let player = (self.first_player() + i) % num_players;
+            let bid = self.blind_bids[player].take();
+            self.seats[player].bid = bid;
+            if let Some(bid) = bid {
+                self.events.push(GameEvent::BidPlaced { player, bid });
+            }
+        }
+
+        self.turn = Turn::PlayCard(self.current_leader);
+    }
+
+    /// Play a card from a player's hand into the current trick
+    ///
+    /// A Juggler or Cloud must be played with the suit the player chose for it, e.g. `CloudH`.
+    ///
+    /// # Returns
+    ///
+    /// The player who won the trick if this card completed it. Nobody wins a trick with a Bomb.
+    pub fn play_card(&mut self, player: usize, card: Card) -> Result<Option<usize>> {
+        if self.turn != Turn::PlayCard(player) {
+            bail!(RuleViolation::NotYourTurn { player });
+        }
+
+        let held_card = card.without_chosen_suit();
+        let index = match self.seats[player]
+            .cards
+            .iter()
+            .position(|c| *c == held_card)
+        {
+            Some(index) => index,
+            None => bail!(RuleViolation::CardNotInHand { card }),
+        };
+        let playable_cards = self.playable_cards(player);
+        if !playable_cards.contains(&held_card) {
+            bail!(RuleViolation::CardNotPlayable {
+                card,
+                playable_cards,
+            });
+        }
+        if card.needs_suit() {
+            bail!(RuleViolation::SuitNotChosen { card });
+        }
+
+        // Remove played card from hand
+        self.seats[player].cards.remove(index);
+        self.played_cards.push(card);
+        self.events.push(GameEvent::CardPlayed { player, card });
+
+        // Trick is still in progress
+        if self.played_cards.len() < self.seats.len() {
+            self.turn = Turn::PlayCard((player + 1) % self.seats.len());
+            return Ok(None);
+        }
+
+        // Update taken tricks. The winner leads the next trick.
+        let cards = std::mem::take(&mut self.played_cards);
+        let winning_player = self.trick_winner(&cards, self.current_leader);
+        self.current_leader = winning_player;
+
+        let has = |special_card: fn(Option<Suit>) -> SpecialCard| {
+            cards
+                .iter()
+                .any(|card| card.without_chosen_suit() == Card::SpecialCard(special_card(None)))
+        };
+        let cloud = has(SpecialCard::Cloud);
+        let juggler = has(SpecialCard::Juggler);
+
+        // The player who would have won a bombed trick still leads the next one
+        let winner = if cards.contains(&Card::SpecialCard(SpecialCard::Bomb)) {
+            self.events.push(GameEvent::TrickBombed {
+                next_leader: winning_player,
+                cards,
+            });
+            None
+        } else {
+            self.seats[winning_player].won_trick();
+            self.events.push(GameEvent::TrickWon {
+                player: winning_player,
+                cards,
+            });
+            Some(winning_player)
+        };
+
+        if cloud {
+            self.bid_adjustment = winner;
+        }
+        if juggler && !self.seats[0].cards.is_empty() {
+            self.passed_cards = Some(vec![None; self.seats.len()]);
+        }
+        self.finish_trick()?;
+
+        Ok(winner)
+    }
+
+    /// Raise (`1`) or lower (`-1`) a bid after winning a trick with a Cloud
+    pub fn adjust_bid(&mut self, player: usize, adjustment: i8) -> Result<()> {
+        if self.turn != Turn::AdjustBid(player) {
+            bail!(RuleViolation::NotYourTurn { player });
+        }
+
+        let bid = self.seats[player].bid.unwrap_or(0);
+        let adjusted = match adjustment {
+            1 => bid.checked_add(1),
+            -1 => bid.checked_sub(1),
+            _ => None,
+        };
+        let adjusted = match adjusted {
+            Some(adjusted) => adjusted,
+            None => bail!(RuleViolation::InvalidBidAdjustment { bid, adjustment }),
+        };
+
+        self.seats[player].bid = Some(adjusted);
+        self.events
+            .push(GameEvent::BidAdjusted { player, adjustment });
+        self.bid_adjustment = None;
+
+        self.finish_trick()
+    }
+
+    /// Choose the card to pass to the player on the left after a Juggler was played
+    ///
+    /// Passed cards are handed over once every player has chosen one.
+    pub fn pass_card(&mut self, player: usize, card: Card) -> Result<()> {
+        if self.turn != Turn::PassCard(player) {
+            bail!(RuleViolation::NotYourTurn { player });
+        }
+
+        let index = match self.seats[player].cards.iter().position(|c| *c == card) {
+            Some(index) => index,
+            None => bail!(RuleViolation::CardNotInHand { card }),
+        };
+
+        self.seats[player].cards.remove(index);
+        if let Some(passed_cards) = &mut self.passed_cards {
+            passed_cards[player] = Some(card);
+        }
+        self.events.push(GameEvent::CardPassed {
+            player,
+            card: Some(card),
+        });
+
+        self.finish_trick()
+    }
+
+    /// Resolve what a completed trick set off, then start the next trick or round
+    fn finish_trick(&mut self) -> Result<()> {
+        if let Some(player) = self.bid_adjustment {
+            self.turn = Turn::AdjustBid(player);
+            return Ok(());
+        }
+
+        if let Some(passed_cards) = self.passed_cards.take() {
+            // Players pass in turn order starting with the next leader
+            let num_players = self.seats.len();
+            let next = (0..num_players)
+                .map(|i| (self.current_leader + i) % num_players)
+                .find(|player| passed_cards[*player].is_none());
+            if let Some(player) = next {
+                self.passed_cards = Some(passed_cards);
+                self.turn = Turn::PassCard(player);
+                return Ok(());
+            }
+
+            for (player, card) in passed_cards.into_iter().enumerate() {
+                if let Some(card) = card {
+                    self.seats[(player + 1) % num_players].cards.push(card);
+                }
+            }
+        }
+
+        if !self.seats[0].cards.is_empty() {
+            self.turn = Turn::PlayCard(self.current_leader);
+            return Ok(());
+        }
+
+        // Round is over
+        self.update_player_scores();
+        self.events.push(GameEvent::RoundScored {
+            round: self.round,
+            scores: self.scores(),
+        });
+        if self.round == self.num_rounds {
+            self.events.push(GameEvent::GameEnded {
+                scores: self.scores(),
+            });
+            self.turn = Turn::GameOver;
+        } else {
+            self.start_round()?;
+        }
+
+        Ok(())
+    }
+
+    /// List of playable cards given the current hand and what has been played
+    pub fn playable_cards(&self, player: usize) -> Vec<Card> {
+        let hand = &self.seats[player].cards;
+        let lead_suit = GameEngine::leading_suit(&self.played_cards);
+
+        let has_lead_suit = hand.iter().any(|card| {
+            matches!(card, Card::NormalCard(normal_card) if Some(normal_card.suit) == lead_suit)
+        });
+
+        // If hand does not contain lead suit, they can play whatever card
+        if !has_lead_suit {
+            return hand.clone();
+        }
+
+        hand.iter()
+            .filter(|card| match card {
+                Card::SpecialCard(_) => true,
+                Card::NormalCard(normal_card) => Some(normal_card.suit) == lead_suit,
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Leading suit given a vector of cards.
+    ///
+    /// If a Wizard or Dragon has been played, then leading suit is set to none since suit no longer
+    /// matters.
+    pub fn leading_suit(cards: &[Card]) -> Option<Suit> {
+        // If there are any wizards, then there is no leading suit
+        if cards.contains(&Card::SpecialCard(SpecialCard::Wizard))
+            || cards.contains(&Card::SpecialCard(SpecialCard::Dragon))
+        {
+            return None;
+        }
+
+        // Return the suit of the first card with a suit
+        for card in cards.iter() {
+            if let Some((suit, _)) = GameEngine::suit_and_value(card) {
+                return Some(suit);
+            }
+        }
+
+        // If there are only jesters, there is no trump suit
+        None
+    }
+
+    fn trick_winner(&self, cards: &[Card], leading_player: usize) -> usize {
+        (GameEngine::winning_card(cards, self.trump_suit) + leading_player) % self.seats.len()
+    }
+
+    /// Position of the card that takes a trick, with `cards` in the order they were played
+    ///
+    /// A played Juggler or Cloud must have its chosen suit. Does not account for a Bomb, which
+    /// stops anyone from taking the trick.
+    pub fn winning_card(cards: &[Card], trump_suit: Option<Suit>) -> usize {
+        let mut winning = 0;
+        let leading_suit = GameEngine::leading_suit(cards);
+
+        for (i, new_card) in cards.iter().enumerate().skip(1) {
+            if GameEngine::is_better_card(&cards[winning], new_card, leading_suit, trump_suit) {
+                winning = i;
+            }
+        }
+
+        // The Fairy is the only card that beats the Dragon
+        if cards[winning] == Card::SpecialCard(SpecialCard::Dragon) {
+            if let Some(i) = cards
+                .iter()
+                .position(|card| *card == Card::SpecialCard(SpecialCard::Fairy))
+            {
+                winning = i;
+            }
+        }
+
+        winning
+    }
+
+    /// Suit of a card and its value within the suit, if it has one
+    ///
+    /// Values are scaled so the Juggler sits between the 7 and 8 and the Cloud between the 9 and
+    /// 10 of their chosen suit.
+    fn suit_and_value(card: &Card) -> Option<(Suit, u8)> {
+        match card {
+            Card::NormalCard(normal_card) => Some((normal_card.suit, normal_card.rank.value() * 4)),
+            Card::SpecialCard(SpecialCard::Juggler(Some(suit))) => Some((*suit, 7 * 4 + 2)),
+            Card::SpecialCard(SpecialCard::Cloud(Some(suit))) => Some((*suit, 9 * 4 + 3)),
+            Card::SpecialCard(_) => None,
+        }
+    }
+
+    /// Checks to see if a card is better than another card.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_card` - Card to compare to
+    /// * `is_better` - Card to compare
+    /// * `leading_suit` - Suit that has been lead
+    /// * `trump_suit` - Trump suit
+    ///
+    /// # Returns
+    ///
+    /// True if `is_better` is a better card than `base_card`
+    fn is_better_card(
+        base_card: &Card,
+        is_better: &Card,
+        leading_suit: Option<Suit>,
+        trump_suit: Option<Suit>,
+    ) -> bool {
+        // base_card = Dragon always is better, then a Wizard
+        // is_better = Dragon or Wizard -> Wins if first card isn't better
+        for top_card in [SpecialCard::Dragon, SpecialCard::Wizard] {
+            if *base_card == Card::SpecialCard(top_card) {
+                return false;
+            }
+            if *is_better == Card::SpecialCard(top_card) {
+                return true;
+            }
+        }
+
+        // Cards without a suit (Jester, Fairy, Bomb) lose to any card with a suit. If neither card
+        // has a suit, the first card wins.
+        let (base_suit, base_value) = match GameEngine::suit_and_value(base_card) {
+            Some(suit_and_value) => suit_and_value,
+            None => return GameEngine::suit_and_value(is_better).is_some(),
+        };
+        let (is_better_suit, is_better_value) = match GameEngine::suit_and_value(is_better) {
+            Some(suit_and_value) => suit_and_value,
+            None => return false,
+        };
+
+        // Same suit, high card wins
+        if base_suit == is_better_suit {
+            return base_value < is_better_value;
+        }
+        // Different suit, trump suit wins first, then leading suit
+        if Some(base_suit) == trump_suit {
+            return false;
+        }
+        if Some(is_better_suit) == trump_suit {
+            return true;
+        }
+        if Some(base_suit) == leading_suit {
+            return false;
+        }
+        if Some(is_better_suit) == leading_suit {
+            return true;
+        }
+
+        // Default to first card winning
+        false
+    }
+
+    /// Update each players' scores based on the results of the round
+    fn update_player_scores(&mut self) {
+        for seat in self.seats.iter_mut() {
+            seat.update_score(self.scoring.as_ref(), self.round, self.num_rounds);
+        }
+    }
+
+    /// Score of every player
+    fn scores(&self) -> Vec<i32> {
+        self.seats.iter().map(|seat| seat.score).collect()
+    }
+
+    /// Public game state
+    pub fn game_state(&self) -> GameState {
+        GameState {
+            round: self.round,
+            num_rounds: self.num_rounds,
+            hand_size: self.hand_size(),
+            // Knowing the seed reveals every hand, so only share it once the game is over
+            seed: match self.turn {
+                Turn::GameOver => Some(self.seed),
+                _ => None,
+            },
+            dealer: self.dealer,
+            trump_suit: self.trump_suit,
+            player_count: self.seats.len(),
+            players: self
+                .seats
+                .iter()
+                .map(|seat| PlayerState {
+                    name: seat.name.clone(),
+                    score: seat.score,
+                    bid: seat.bid,
+                    tricks_taken: seat.tricks_taken,
+                })
+                .collect(),
+            played_cards: self.played_cards.clone(),
+            current_leader: self.current_leader,
+            leading_suit: GameEngine::leading_suit(&self.played_cards),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::deck::DeckSpec;
+    use crate::cards::normal_card::NormalCard;
+    use crate::game::rules::RoundSchedule;
+    use crate::game::scoring::{ScoringScheme, StandardScoring};
+    use strum::IntoEnumIterator;
+
+    /// Play the first playable card for whoever's turn it is. Returns false once the game is over.
+    fn step(engine: &mut GameEngine) -> bool {
+        match engine.turn() {
+            Turn::ChooseTrump(i) => engine.choose_trump(i, Suit::Heart).unwrap(),
+            Turn::Bid(i) => engine.bid(i, 0).unwrap(),
+            Turn::BlindBid => engine.bid(engine.pending_bidders()[0], 0).unwrap(),
+            Turn::PlayCard(i) => {
+                let card = engine.playable_cards(i)[0].with_chosen_suit(Suit::Heart);
+                engine.play_card(i, card).unwrap();
+            }
+            Turn::AdjustBid(i) => engine.adjust_bid(i, 1).unwrap(),
+            Turn::PassCard(i) => engine.pass_card(i, engine.hand(i)[0]).unwrap(),
+            Turn::GameOver => return false,
+        }
+        true
+    }
+
+    /// Three player game at the first trick of round 2, where player 2 leads
+    fn second_round_first_trick(rules: GameRules) -> GameEngine {
+        let mut engine = GameEngine::with_rules(3, 0, rules).unwrap();
+        while engine.round() < 2 || !matches!(engine.turn(), Turn::PlayCard(_)) {
+            step(&mut engine);
+        }
+        assert_eq!(Turn::PlayCard(2), engine.turn());
+        engine
+    }
+
+    fn anniversary_rules() -> GameRules {
+        GameRules {
+            special_cards: vec![
+                SpecialCard::Bomb,
+                SpecialCard::Dragon,
+                SpecialCard::Fairy,
+                SpecialCard::Werewolf,
+                SpecialCard::Juggler(None),
+                SpecialCard::Cloud(None),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn not_enough_players() {
+        assert!(GameEngine::new(1).is_err());
+        assert!(GameEngine::new(0).is_err());
+    }
+
+    #[test]
+    fn too_many_players() {
+        assert!(GameEngine::new(MAX_PLAYERS + 1).is_err());
+        assert!(GameEngine::new(MAX_PLAYERS).is_ok());
+
+        // A deck with fewer cards than players is rejected below the cap too
+        let rules = GameRules {
+            deck: Some(DeckSpec {
+                suits: vec![Suit::Heart],
+                removed_ranks: Rank::iter().take(8).collect(),
+                wizards: 0,
+                jesters: 0,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert!(GameEngine::with_rules(6, 0, rules.clone()).is_err());
+        assert!(GameEngine::with_rules(5, 0, rules).is_ok());
+    }
+
+    #[test]
+    fn table_sizes_from_two_to_eight_players() {
+        for num_players in 2..=8 {
+            // Without a deck in the rules the table gets the one suited to its size
+            let mut engine = GameEngine::with_rules(num_players, 0, GameRules::default()).unwrap();
+            let deck = DeckSpec::for_table(num_players);
+            assert_eq!(Some(&deck), engine.rules().deck.as_ref());
+            assert_eq!((deck.len() / num_players) as u8, engine.num_rounds());
+            while step(&mut engine) {}
+        }
+
+        // A deck in the rules is played whatever the table size
+        let standard_deck = GameRules {
+            deck: Some(DeckSpec::default()),
+            ..Default::default()
+        };
+        assert_eq!(
+            8,
+            GameEngine::with_rules(7, 0, standard_deck)
+                .unwrap()
+                .num_rounds()
+        );
+
+        // Two players get a reduced deck and seven or eight an extended one
+        assert_eq!(
+            22,
+            GameEngine::with_rules(2, 0, GameRules::for_table(2))
+                .unwrap()
+                .num_rounds()
+        );
+        assert_eq!(
+            20,
+            GameEngine::with_rules(3, 0, GameRules::for_table(3))
+                .unwrap()
+                .num_rounds()
+        );
+        assert_eq!(
+            9,
+            GameEngine::with_rules(7, 0, GameRules::for_table(7))
+                .unwrap()
+                .num_rounds()
+        );
+        assert_eq!(
+            8,
+            GameEngine::with_rules(8, 0, GameRules::for_table(8))
+                .unwrap()
+                .num_rounds()
+        );
+    }
+
+    #[test]
+    fn player_creation() {
+        for num_players in 2..=8 {
+            let engine = GameEngine::new(num_players).unwrap();
+
+            assert_eq!(num_players, engine.num_players());
+            assert_eq!(1, engine.round());
+
+            for seat in engine.seats() {
+                assert_eq!(0, seat.score);
+                assert_eq!(1, seat.cards.len());
+            }
+        }
+    }
+
+    #[test]
+    fn play_four_player_game() {
+        let mut engine = GameEngine::new(4).unwrap();
+
+        while step(&mut engine) {}
+
+        assert_eq!(15, engine.round());
+        assert_eq!(Turn::GameOver, engine.turn());
+        for seat in engine.seats() {
+            assert!(seat.cards.is_empty());
+            assert_eq!(None, seat.bid);
+        }
+    }
+
+    #[test]
+    fn same_seed_same_game() {
+        let mut engine = GameEngine::with_seed(4, 42).unwrap();
+        let mut engine2 = GameEngine::with_seed(4, 42).unwrap();
+
+        loop {
+            assert_eq!(engine.turn(), engine2.turn());
+            assert_eq!(engine.trump_suit(), engine2.trump_suit());
+            for i in 0..engine.num_players() {
+                assert_eq!(engine.hand(i), engine2.hand(i));
+            }
+            step(&mut engine2);
+            if !step(&mut engine) {
+                break;
+            }
+        }
+
+        assert_eq!(engine.game_state(), engine2.game_state());
+        assert_eq!(Some(42), engine.game_state().seed);
+    }
+
+    #[test]
+    fn seed_hidden_until_game_over() {
+        let engine = GameEngine::with_seed(3, 42).unwrap();
+        let engine2 = GameEngine::with_seed(3, 43).unwrap();
+
+        assert_eq!(42, engine.seed());
+        assert_eq!(None, engine.game_state().seed);
+        assert_ne!(
+            (0..3).map(|i| engine.hand(i)).collect::<Vec<_>>(),
+            (0..3).map(|i| engine2.hand(i)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn dealer_rotates_every_round() {
+        let mut engine = GameEngine::new(4).unwrap();
+
+        loop {
+            let round = engine.round();
+            let dealer = (round as usize - 1) % 4;
+            assert_eq!(dealer, engine.dealer());
+            assert_eq!(dealer, engine.game_state().dealer);
+
+            // Dealer chooses trump, the player to their left bids and leads first
+            if let Turn::ChooseTrump(i) = engine.turn() {
+                assert_eq!(dealer, i);
+                step(&mut engine);
+            }
+            assert_eq!(Turn::Bid((dealer + 1) % 4), engine.turn());
+            while matches!(engine.turn(), Turn::Bid(_)) {
+                step(&mut engine);
+            }
+            assert_eq!(Turn::PlayCard((dealer + 1) % 4), engine.turn());
+            assert_eq!((dealer + 1) % 4, engine.game_state().current_leader);
+
+            while engine.round() == round && step(&mut engine) {}
+            if engine.turn() == Turn::GameOver {
+                break;
+            }
+        }
+
+        assert_eq!(15, engine.round());
+    }
+
+    #[test]
+    fn trick_winner_leads_next_trick() {
+        let wizard = Card::SpecialCard(SpecialCard::Wizard);
+        let jester = Card::SpecialCard(SpecialCard::Jester);
+        let mut engine = GameEngine::new(3).unwrap();
+        // Play until the second round's first trick starts
+        while engine.round() < 2 || !matches!(engine.turn(), Turn::PlayCard(_)) {
+            step(&mut engine);
+        }
+        assert_eq!(1, engine.dealer());
+        assert_eq!(Turn::PlayCard(2), engine.turn());
+
+        engine.seats[0].cards = vec![wizard, wizard];
+        engine.seats[1].cards = vec![jester, jester];
+        engine.seats[2].cards = vec![jester, jester];
+
+        // Player 2 leads but player 0 wins the trick
+        assert_eq!(None, engine.play_card(2, jester).unwrap());
+        assert_eq!(None, engine.play_card(0, wizard).unwrap());
+        assert_eq!(Some(0), engine.play_card(1, jester).unwrap());
+
+        assert_eq!(0, engine.current_leader());
+        assert_eq!(Turn::PlayCard(0), engine.turn());
+        assert!(engine.play_card(2, jester).is_err());
+    }
+
+    #[test]
+    fn bidding_order_wraps_around_the_table() {
+        let mut engine = GameEngine::new(4).unwrap();
+        // Play until the third round, where player 2 deals and player 3 bids first
+        while engine.round() < 3 || !matches!(engine.turn(), Turn::Bid(_)) {
+            step(&mut engine);
+        }
+        assert!(engine.bids().is_empty());
+
+        for (player, bid) in [(3, 1), (0, 0), (1, 3), (2, 2)] {
+            assert_eq!(Turn::Bid(player), engine.turn());
+            engine.bid(player, bid).unwrap();
+        }
+
+        assert_eq!(
+            vec![
+                PlacedBid { player: 3, bid: 1 },
+                PlacedBid { player: 0, bid: 0 },
+                PlacedBid { player: 1, bid: 3 },
+                PlacedBid { player: 2, bid: 2 },
+            ],
+            engine.bids()
+        );
+        assert_eq!(Turn::PlayCard(3), engine.turn());
+    }
+
+    #[test]
+    fn bids_only_include_players_who_have_bid() {
+        let mut engine = GameEngine::new(3).unwrap();
+        while !matches!(engine.turn(), Turn::Bid(_)) {
+            step(&mut engine);
+        }
+
+        engine.bid(1, 1).unwrap();
+        assert_eq!(vec![PlacedBid { player: 1, bid: 1 }], engine.bids());
+        assert_eq!(Turn::Bid(2), engine.turn());
+    }
+
+    #[test]
+    fn events_follow_the_game() {
+        let mut engine = GameEngine::with_seed(3, 7).unwrap();
+        while step(&mut engine) {}
+        let events = engine.events();
+
+        assert_eq!(
+            GameEvent::GameStarted {
+                num_players: 3,
+                seed: Some(7),
+                rules: GameRules::for_table(3),
+            },
+            events[0]
+        );
+        assert!(matches!(
+            events[1],
+            GameEvent::CardsDealt {
+                round: 1,
+                dealer: 0,
+                ..
+            }
+        ));
+        assert!(matches!(events[2], GameEvent::TrumpRevealed { .. }));
+        assert_eq!(
+            Some(&GameEvent::GameEnded {
+                scores: engine.seats().iter().map(|seat| seat.score).collect()
+            }),
+            events.last()
+        );
+        let tricks = events
+            .iter()
+            .filter(|event| matches!(event, GameEvent::TrickWon { .. }))
+            .count();
+        assert_eq!((1..=20).sum::<usize>(), tricks);
+    }
+
+    #[test]
+    fn replay_rebuilds_every_point_of_the_game() {
+        let mut engine = GameEngine::new(4).unwrap();
+        let mut states = vec![(engine.events().len(), engine.game_state())];
+        while step(&mut engine) {
+            states.push((engine.events().len(), engine.game_state()));
+        }
+
+        for (len, state) in states {
+            let replayed = GameEngine::replay(&engine.events()[..len]).unwrap();
+            assert_eq!(state, replayed.game_state());
+        }
+        let replayed = GameEngine::replay(engine.events()).unwrap();
+        assert_eq!(engine.events(), replayed.events());
+        assert_eq!(Turn::GameOver, replayed.turn());
+    }
+
+    #[test]
+    fn replay_rejects_tampered_events() {
+        let mut engine = GameEngine::with_seed(3, 1).unwrap();
+        for _ in 0..10 {
+            step(&mut engine);
+        }
+        let mut events = engine.events().to_vec();
+
+        assert!(GameEngine::replay(&events[1..]).is_err());
+
+        // Hand out a different deal than the seed produces
+        if let GameEvent::CardsDealt { hands, .. } = &mut events[1] {
+            hands.swap(0, 1);
+        }
+        assert!(GameEngine::replay(&events).is_err());
+
+        let redacted: Vec<GameEvent> = engine.events().iter().map(|e| e.redacted()).collect();
+        assert!(GameEngine::replay(&redacted).is_err());
+    }
+
+    #[test]
+    fn position_plays_out_like_the_game() {
+        let hands = |engine: &GameEngine| (0..4).map(|i| engine.hand(i).to_vec()).collect();
+        let mut engine = GameEngine::with_seed(4, 5).unwrap();
+        while engine.round() < 3 || engine.played_cards().len() < 2 {
+            step(&mut engine);
+        }
+
+        let mut position = GameEngine::from_position(
+            &engine.game_state(),
+            hands(&engine),
+            GameRules::default(),
+            9,
+        )
+        .unwrap();
+        assert_eq!(engine.turn(), position.turn());
+        assert!(position.events().is_empty());
+        while engine.round() == 3 {
+            step(&mut engine);
+            step(&mut position);
+        }
+        assert_eq!(engine.round(), position.round());
+        assert_eq!(engine.game_state().players, position.game_state().players);
+
+        // Part way through bidding
+        step(&mut engine);
+        let position = GameEngine::from_position(
+            &engine.game_state(),
+            hands(&engine),
+            GameRules::default(),
+            9,
+        )
+        .unwrap();
+        assert_eq!(Turn::Bid(1), position.turn());
+
+        let mut short: Vec<Vec<Card>> = hands(&engine);
+        short[2].pop();
+        assert!(
+            GameEngine::from_position(&engine.game_state(), short, GameRules::default(), 9)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn restricted_bidding_forbids_last_bid_matching_tricks() {
+        let rules = GameRules {
+            restricted_bidding: true,
+            ..Default::default()
+        };
+        let mut engine = GameEngine::with_rules(3, 5, rules).unwrap();
+        // Play until the third round, where player 2 deals and bids last
+        while engine.round() < 3 || !matches!(engine.turn(), Turn::Bid(_)) {
+            match engine.turn() {
+                Turn::Bid(i) => {
+                    let bid = if engine.forbidden_bid() == Some(0) {
+                        1
+                    } else {
+                        0
+                    };
+                    engine.bid(i, bid).unwrap();
+                }
+                _ => {
+                    step(&mut engine);
+                }
+            }
+        }
+
+        assert_eq!(None, engine.forbidden_bid());
+        engine.bid(0, 1).unwrap();
+        assert_eq!(None, engine.forbidden_bid());
+        engine.bid(1, 0).unwrap();
+        assert_eq!(Some(2), engine.forbidden_bid());
+
+        let error = engine.bid(2, 2).unwrap_err();
+        assert_eq!(
+            Some(&RuleViolation::ForbiddenBid { bid: 2 }),
+            error.downcast_ref::<RuleViolation>()
+        );
+        assert_eq!(Turn::Bid(2), engine.turn());
+        engine.bid(2, 3).unwrap();
+    }
+
+    #[test]
+    fn restricted_bidding_allows_any_bid_once_overbid() {
+        let rules = GameRules {
+            restricted_bidding: true,
+            ..Default::default()
+        };
+        let mut engine = GameEngine::with_rules(3, 5, rules).unwrap();
+        while !matches!(engine.turn(), Turn::Bid(_)) {
+            step(&mut engine);
+        }
+
+        engine.bid(1, 1).unwrap();
+        engine.bid(2, 1).unwrap();
+        assert_eq!(None, engine.forbidden_bid());
+        engine.bid(0, 1).unwrap();
+    }
+
+    #[test]
+    fn unrestricted_bidding_forbids_nothing() {
+        let mut engine = GameEngine::with_seed(3, 5).unwrap();
+        while !matches!(engine.turn(), Turn::Bid(_)) {
+            step(&mut engine);
+        }
+
+        engine.bid(1, 0).unwrap();
+        engine.bid(2, 0).unwrap();
+        assert_eq!(None, engine.forbidden_bid());
+        engine.bid(0, 1).unwrap();
+    }
+
+    #[test]
+    fn total_score_saturates() {
+        let mut seat = Seat {
+            score: i32::MAX - 5,
+            bid: Some(1),
+            tricks_taken: 1,
+            ..Default::default()
+        };
+        seat.update_score(&StandardScoring, 1, 1);
+        assert_eq!(i32::MAX, seat.score);
+    }
+
+    #[test]
+    fn rounds_are_scored_with_the_game_scoring_scheme() {
+        let rules = GameRules {
+            scoring: ScoringScheme::FlatPenalty { penalty: 50 },
+            ..Default::default()
+        };
+        let mut standard = GameEngine::with_seed(3, 9).unwrap();
+        let mut flat_penalty = GameEngine::with_rules(3, 9, rules).unwrap();
+        while standard.round() == 1 {
+            step(&mut standard);
+            step(&mut flat_penalty);
+        }
+
+        // Everyone bids zero in the first round, so exactly one player misses their bid
+        for (standard, flat_penalty) in standard.seats().iter().zip(flat_penalty.seats()) {
+            match standard.score {
+                20 => assert_eq!(20, flat_penalty.score),
+                -10 => assert_eq!(-50, flat_penalty.score),
+                score => panic!("Unexpected score {}", score),
+            }
+        }
+    }
+
+    #[test]
+    fn unregistered_scoring_scheme_is_rejected() {
+        let rules = GameRules {
+            scoring: ScoringScheme::Custom {
+                name: "not_registered".to_string(),
+            },
+            ..Default::default()
+        };
+        assert!(GameEngine::with_rules(3, 0, rules).is_err());
+    }
+
+    #[test]
+    fn anniversary_game_plays_to_the_end_and_replays() {
+        let mut engine = GameEngine::with_rules(4, 3, anniversary_rules()).unwrap();
+        while step(&mut engine) {}
+
+        let replayed = GameEngine::replay(engine.events()).unwrap();
+        assert_eq!(engine.game_state(), replayed.game_state());
+    }
+
+    #[test]
+    fn only_distinct_anniversary_cards_can_be_added() {
+        for special_cards in [
+            vec![SpecialCard::Wizard],
+            vec![SpecialCard::Bomb, SpecialCard::Bomb],
+            vec![SpecialCard::Cloud(Some(Suit::Heart))],
+        ] {
+            let rules = GameRules {
+                special_cards,
+                ..Default::default()
+            };
+            assert!(GameEngine::with_rules(3, 0, rules).is_err());
+        }
+    }
+
+    #[test]
+    fn dragon_beats_wizards_unless_a_fairy_is_played() {
+        let engine = GameEngine::new(3).unwrap();
+        let wizard = Card::SpecialCard(SpecialCard::Wizard);
+        let dragon = Card::SpecialCard(SpecialCard::Dragon);
+        let fairy = Card::SpecialCard(SpecialCard::Fairy);
+        let ace = Card::NormalCard(NormalCard {
+            suit: Suit::Spade,
+            rank: Rank::Ace,
+        });
+
+        assert_eq!(1, engine.trick_winner(&[wizard, dragon, ace], 0));
+        assert_eq!(2, engine.trick_winner(&[wizard, dragon, fairy], 0));
+        assert_eq!(1, engine.trick_winner(&[fairy, ace, wizard], 2));
+        assert_eq!(0, engine.trick_winner(&[fairy, fairy, fairy], 0));
+    }
+
+    #[test]
+    fn juggler_and_cloud_rank_in_their_chosen_suit() {
+        // A deal without trump, so only the chosen suits decide the tricks
+        let engine = (0..)
+            .map(|seed| GameEngine::with_seed(3, seed).unwrap())
+            .find(|engine| engine.game_state().trump_suit.is_none())
+            .unwrap();
+        let card = |suit, rank| Card::NormalCard(NormalCard { suit, rank });
+        let juggler = Card::SpecialCard(SpecialCard::Juggler(Some(Suit::Club)));
+        let cloud = Card::SpecialCard(SpecialCard::Cloud(Some(Suit::Club)));
+
+        assert_eq!(
+            1,
+            engine.trick_winner(
+                &[
+                    card(Suit::Club, Rank::Seven),
+                    juggler,
+                    card(Suit::Heart, Rank::Ace)
+                ],
+                0
+            )
+        );
+        assert_eq!(
+            0,
+            engine.trick_winner(&[card(Suit::Club, Rank::Eight), juggler, cloud], 1)
+        );
+        assert_eq!(
+            2,
+            engine.trick_winner(&[juggler, card(Suit::Club, Rank::Nine), cloud], 0)
+        );
+        assert_eq!(
+            Some(Suit::Club),
+            GameEngine::leading_suit(&[Card::SpecialCard(SpecialCard::Jester), cloud])
+        );
+    }
+
+    #[test]
+    fn bombed_trick_is_won_by_nobody() {
+        let mut engine = second_round_first_trick(GameRules::default());
+        let bomb = Card::SpecialCard(SpecialCard::Bomb);
+        let wizard = Card::SpecialCard(SpecialCard::Wizard);
+        let jester = Card::SpecialCard(SpecialCard::Jester);
+        engine.seats[0].cards = vec![wizard, jester];
+        engine.seats[1].cards = vec![jester, jester];
+        engine.seats[2].cards = vec![bomb, jester];
+
+        assert_eq!(None, engine.play_card(2, bomb).unwrap());
+        assert_eq!(None, engine.play_card(0, wizard).unwrap());
+        assert_eq!(None, engine.play_card(1, jester).unwrap());
+
+        // The Wizard would have won, so player 0 leads without taking the trick
+        assert!(engine.seats().iter().all(|seat| seat.tricks_taken == 0));
+        assert_eq!(Turn::PlayCard(0), engine.turn());
+        assert_eq!(
+            Some(&GameEvent::TrickBombed {
+                next_leader: 0,
+                cards: vec![bomb, wizard, jester],
+            }),
+            engine.events().last()
+        );
+    }
+
+    #[test]
+    fn cloud_trick_winner_adjusts_bid() {
+        let mut engine = second_round_first_trick(GameRules::default());
+        let cloud = Card::SpecialCard(SpecialCard::Cloud(None));
+        let jester = Card::SpecialCard(SpecialCard::Jester);
+        engine.seats[0].cards = vec![jester, jester];
+        engine.seats[1].cards = vec![jester, jester];
+        engine.seats[2].cards = vec![cloud, jester];
+
+        let error = engine.play_card(2, cloud).unwrap_err();
+        assert_eq!(
+            Some(&RuleViolation::SuitNotChosen { card: cloud }),
+            error.downcast_ref::<RuleViolation>()
+        );
+        assert_eq!(
+            None,
+            engine
+                .play_card(2, cloud.with_chosen_suit(Suit::Heart))
+                .unwrap()
+        );
+        engine.play_card(0, jester).unwrap();
+        assert_eq!(Some(2), engine.play_card(1, jester).unwrap());
+
+        // Everyone bid 0, so the bid can only be raised
+        assert_eq!(Turn::AdjustBid(2), engine.turn());
+        assert!(engine.adjust_bid(2, -1).is_err());
+        assert!(engine.adjust_bid(2, 2).is_err());
+        engine.adjust_bid(2, 1).unwrap();
+        assert_eq!(Some(1), engine.seats()[2].bid);
+        assert_eq!(Turn::PlayCard(2), engine.turn());
+    }
+
+    #[test]
+    fn juggler_makes_everyone_pass_a_card_left() {
+        let mut engine = second_round_first_trick(GameRules::default());
+        let juggler = Card::SpecialCard(SpecialCard::Juggler(None));
+        let card = |rank| {
+            Card::NormalCard(NormalCard {
+                suit: Suit::Club,
+                rank,
+            })
+        };
+        engine.seats[0].cards = vec![card(Rank::Two), card(Rank::Three)];
+        engine.seats[1].cards = vec![card(Rank::Four), card(Rank::Five)];
+        engine.seats[2].cards = vec![juggler, card(Rank::Six)];
+
+        engine
+            .play_card(2, juggler.with_chosen_suit(Suit::Club))
+            .unwrap();
+        engine.play_card(0, card(Rank::Two)).unwrap();
+        assert_eq!(Some(2), engine.play_card(1, card(Rank::Four)).unwrap());
+
+        // Passing starts with the trick winner and cards are handed over once everyone has passed
+        assert_eq!(Turn::PassCard(2), engine.turn());
+        engine.pass_card(2, card(Rank::Six)).unwrap();
+        assert!(engine.pass_card(1, card(Rank::Six)).is_err());
+        assert!(engine.pass_card(0, card(Rank::Six)).is_err());
+        engine.pass_card(0, card(Rank::Three)).unwrap();
+        engine.pass_card(1, card(Rank::Five)).unwrap();
+
+        assert_eq!(&[card(Rank::Five)], engine.hand(2));
+        assert_eq!(&[card(Rank::Six)], engine.hand(0));
+        assert_eq!(&[card(Rank::Three)], engine.hand(1));
+        assert_eq!(Turn::PlayCard(2), engine.turn());
+    }
+
+    #[test]
+    fn werewolf_is_swapped_for_the_trump_card() {
+        let werewolf = Card::SpecialCard(SpecialCard::Werewolf);
+        let rules = GameRules {
+            special_cards: vec![SpecialCard::Werewolf],
+            ..Default::default()
+        };
+        // Find a game where someone is dealt the Werewolf in the first round
+        let engine = (0..)
+            .map(|seed| GameEngine::with_rules(3, seed, rules.clone()).unwrap())
+            .find(|engine| {
+                matches!(
+                    engine.events().last(),
+                    Some(GameEvent::WerewolfSwapped { .. })
+                )
+            })
+            .unwrap();
+        let (player, card) = match engine.events().last() {
+            Some(GameEvent::WerewolfSwapped { player, card }) => (*player, *card),
+            _ => unreachable!(),
+        };
+
+        assert_eq!(Turn::ChooseTrump(player), engine.turn());
+        assert_eq!(None, engine.trump_suit());
+        assert_eq!(&[card], engine.hand(player));
+        assert!(engine
+            .seats()
+            .iter()
+            .all(|seat| !seat.cards.contains(&werewolf)));
+    }
+
+    #[test]
+    fn rounds_follow_the_schedule() {
+        let rules = GameRules {
+            rounds: RoundSchedule::UpAndDown,
+            deck: Some(DeckSpec {
+                suits: vec![Suit::Heart, Suit::Spade],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let mut engine = GameEngine::with_rules(4, 0, rules).unwrap();
+        // 26 normal cards and 8 special cards give at most 8 cards each
+        assert_eq!(15, engine.num_rounds());
+
+        let mut hand_sizes = Vec::new();
+        loop {
+            hand_sizes.push(engine.hand_size());
+            assert!(engine
+                .seats()
+                .iter()
+                .all(|seat| seat.cards.len() == engine.hand_size() as usize));
+            let round = engine.round();
+            while engine.round() == round && step(&mut engine) {}
+            if engine.turn() == Turn::GameOver {
+                break;
+            }
+        }
+        assert_eq!(
+            vec![1, 2, 3, 4, 5, 6, 7, 8, 7, 6, 5, 4, 3, 2, 1],
+            hand_sizes
+        );
+    }
+
+    #[test]
+    fn bids_are_limited_by_hand_size() {
+        let rules = GameRules {
+            rounds: RoundSchedule::Custom { rounds: vec![3] },
+            ..Default::default()
+        };
+        let mut engine = GameEngine::with_rules(3, 0, rules).unwrap();
+        while !matches!(engine.turn(), Turn::Bid(_)) {
+            step(&mut engine);
+        }
+
+        assert!(engine.bid(1, 4).is_err());
+        engine.bid(1, 3).unwrap();
+    }
+
+    #[test]
+    fn impossible_deck_or_schedule_is_rejected() {
+        let tiny_deck = GameRules {
+            deck: Some(DeckSpec {
+                wizards: 1,
+                jesters: 1,
+                suits: Vec::new(),
+                removed_ranks: Vec::new(),
+                extra_ranks: Vec::new(),
+            }),
+            ..Default::default()
+        };
+        assert!(GameEngine::with_rules(3, 0, tiny_deck).is_err());
+
+        let too_many_cards = GameRules {
+            rounds: RoundSchedule::Short { rounds: 21 },
+            ..Default::default()
+        };
+        assert!(GameEngine::with_rules(3, 0, too_many_cards).is_err());
+
+        let too_many_rounds = GameRules {
+            rounds: RoundSchedule::Custom {
+                rounds: vec![1; 256],
+            },
+            ..Default::default()
+        };
+        assert!(GameEngine::with_rules(3, 0, too_many_rounds).is_err());
+        let most_rounds = GameRules {
+            rounds: RoundSchedule::Custom {
+                rounds: vec![1; 255],
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            255,
+            GameEngine::with_rules(3, 0, most_rounds)
+                .unwrap()
+                .num_rounds()
+        );
+    }
+
+    #[test]
+    fn extra_suits_and_ranks_are_dealt_and_ranked() {
+        let rules = GameRules {
+            deck: Some(DeckSpec {
+                suits: Suit::iter().chain(Suit::EXTRA).collect(),
+                extra_ranks: vec![Rank::One, Rank::Fifteen],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let mut engine = GameEngine::with_rules(6, 0, rules).unwrap();
+        // 90 normal cards and 8 special cards give at most 16 cards each
+        assert_eq!(16, engine.num_rounds());
+        while step(&mut engine) {}
+
+        let card = |suit, rank| Card::NormalCard(NormalCard { suit, rank });
+        let trick = [
+            card(Suit::Moon, Rank::Ace),
+            card(Suit::Moon, Rank::Fifteen),
+            card(Suit::Heart, Rank::Fifteen),
+            card(Suit::Leaf, Rank::One),
+        ];
+        assert_eq!(1, GameEngine::winning_card(&trick, None));
+        assert_eq!(3, GameEngine::winning_card(&trick, Some(Suit::Leaf)));
+    }
+
+    #[test]
+    fn deck_with_duplicate_entries_is_rejected() {
+        let repeated_suit = GameRules {
+            deck: Some(DeckSpec {
+                suits: vec![Suit::Heart, Suit::Spade, Suit::Heart],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert!(GameEngine::with_rules(3, 0, repeated_suit).is_err());
+
+        let repeated_rank = GameRules {
+            deck: Some(DeckSpec {
+                removed_ranks: vec![Rank::Two, Rank::Two],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert!(GameEngine::with_rules(3, 0, repeated_rank).is_err());
+
+        for extra_ranks in [vec![Rank::Ace], vec![Rank::One, Rank::One]] {
+            let rules = GameRules {
+                deck: Some(DeckSpec {
+                    extra_ranks,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            };
+            assert!(GameEngine::with_rules(3, 0, rules).is_err());
+        }
+    }
+
+    #[test]
+    fn blind_bids_are_hidden_until_everyone_has_bid() {
+        let rules = GameRules {
+            blind_bidding: true,
+            ..Default::default()
+        };
+        let mut engine = GameEngine::with_rules(3, 0, rules).unwrap();
+        while engine.round() < 2 || engine.turn() != Turn::BlindBid {
+            step(&mut engine);
+        }
+        assert_eq!(vec![0, 1, 2], engine.pending_bidders());
+
+        // Any order is fine, but only one bid each
+        engine.bid(0, 2).unwrap();
+        assert!(engine.bid(0, 1).is_err());
+        assert!(engine.bid(1, 3).is_err());
+        engine.bid(1, 1).unwrap();
+        assert_eq!(vec![2], engine.pending_bidders());
+        assert!(engine.bids().is_empty());
+        assert!(engine.game_state().players.iter().all(|p| p.bid.is_none()));
+
+        engine.bid(2, 0).unwrap();
+        assert_eq!(
+            vec![
+                PlacedBid { player: 2, bid: 0 },
+                PlacedBid { player: 0, bid: 2 },
+                PlacedBid { player: 1, bid: 1 },
+            ],
+            engine.bids()
+        );
+        assert_eq!(Turn::PlayCard(2), engine.turn());
+
+        while step(&mut engine) {}
+        let replayed = GameEngine::replay(engine.events()).unwrap();
+        assert_eq!(engine.game_state(), replayed.game_state());
+    }
+
+    #[test]
+    fn blind_bidding_cannot_be_restricted() {
+        let rules = GameRules {
+            blind_bidding: true,
+            restricted_bidding: true,
+            ..Default::default()
+        };
+        assert!(GameEngine::with_rules(3, 0, rules).is_err());
+    }
+
+    #[test]
+    fn out_of_turn_inputs_are_rejected() {
+        let mut engine = GameEngine::new(3).unwrap();
+        if let Turn::ChooseTrump(i) = engine.turn() {
+            engine.choose_trump(i, Suit::Club).unwrap();
+        }
+
+        // Player 0 deals so player 1 bids first
+        assert_eq!(Turn::Bid(1), engine.turn());
+        assert!(engine.bid(2, 0).is_err());
+        assert!(engine.choose_trump(1, Suit::Club).is_err());
+        let card = engine.hand(1)[0];
+        assert!(engine.play_card(1, card).is_err());
+        assert_eq!(Turn::Bid(1), engine.turn());
+    }
+
+    #[test]
+    fn card_not_in_hand_is_rejected() {
+        let mut engine = GameEngine::new(3).unwrap();
+        while !matches!(engine.turn(), Turn::PlayCard(_)) {
+            step(&mut engine);
+        }
+
+        assert_eq!(Turn::PlayCard(1), engine.turn());
+        // Specials come in identical copies, so take a card the player can not be holding
+        let hand = engine.hand(1).to_vec();
+        let other_card = Rank::iter()
+            .map(|rank| {
+                Card::NormalCard(NormalCard {
+                    suit: Suit::Heart,
+                    rank,
+                })
+            })
+            .find(|card| !hand.contains(card))
+            .unwrap();
+        let error = engine.play_card(1, other_card).unwrap_err();
+        assert_eq!(
+            Some(&RuleViolation::CardNotInHand { card: other_card }),
+            error.downcast_ref::<RuleViolation>()
+        );
+        assert_eq!(1, engine.hand(1).len());
+    }
+
+    #[test]
+    fn bid_larger_than_round_is_rejected() {
+        let mut engine = GameEngine::new(3).unwrap();
+        if let Turn::ChooseTrump(i) = engine.turn() {
+            engine.choose_trump(i, Suit::Club).unwrap();
+        }
+
+        let error = engine.bid(1, 2).unwrap_err();
+        assert_eq!(
+            Some(&RuleViolation::InvalidBid {
+                bid: 2,
+                hand_size: 1
+            }),
+            error.downcast_ref::<RuleViolation>()
+        );
+        assert_eq!(Turn::Bid(1), engine.turn());
+        engine.bid(1, 1).unwrap();
+    }
+
+    #[test]
+    fn card_not_following_suit_is_rejected() {
+        let mut engine = GameEngine::new(3).unwrap();
+        let heart = |rank| {
+            Card::NormalCard(NormalCard {
+                suit: Suit::Heart,
+                rank,
+            })
+        };
+        let spade = Card::NormalCard(NormalCard {
+            suit: Suit::Spade,
+            rank: Rank::Ace,
+        });
+        // Player 0 lead a heart
+        engine.seats[0].cards.clear();
+        engine.seats[1].cards = vec![spade, heart(Rank::Three)];
+        engine.played_cards = vec![heart(Rank::Two)];
+        engine.turn = Turn::PlayCard(1);
+
+        let error = engine.play_card(1, spade).unwrap_err();
+        assert_eq!(
+            Some(&RuleViolation::CardNotPlayable {
+                card: spade,
+                playable_cards: vec![heart(Rank::Three)],
+            }),
+            error.downcast_ref::<RuleViolation>()
+        );
+        assert_eq!(2, engine.hand(1).len());
+        assert_eq!(None, engine.play_card(1, heart(Rank::Three)).unwrap());
+    }
+
+    #[test]
+    fn game_state_inital() {
+        let engine = GameEngine::new(4).unwrap();
+
+        let state = engine.game_state();
+
+        assert_eq!(4, state.players.len());
+        for player in state.players {
+            assert_eq!(None, player.bid);
+        }
+    }
+
+    #[test]
+    fn game_state_bid() {
+        let mut engine = GameEngine::new(4).unwrap();
+        while !matches!(engine.turn(), Turn::PlayCard(_)) {
+            step(&mut engine);
+        }
+
+        let state = engine.game_state();
+
+        for player in state.players {
+            assert_eq!(Some(0), player.bid);
+        }
+    }
+
+    #[test]
+    fn trick_winner() {
+        let mut engine = GameEngine::new(3).unwrap();
+
+        // Wizard
+        for i in 0..3 {
+            assert_eq!(
+                i,
+                engine.trick_winner(
+                    &[
+                        Card::SpecialCard(SpecialCard::Wizard),
+                        Card::SpecialCard(SpecialCard::Wizard),
+                        Card::SpecialCard(SpecialCard::Wizard),
+                    ],
+                    i
+                )
+            );
+        }
+        assert_eq!(
+            0,
+            engine.trick_winner(
+                &[
+                    Card::NormalCard(NormalCard {
+                        suit: Suit::Spade,
+                        rank: Rank::Ace
+                    }),
+                    Card::SpecialCard(SpecialCard::Wizard),
+                    Card::NormalCard(NormalCard {
+                        suit: Suit::Spade,
+                        rank: Rank::Queen
+                    }),
+                ],
+                2
+            )
+        );
+        // Jester
+        for i in 0..3 {
+            assert_eq!(
+                i,
+                engine.trick_winner(
+                    &[
+                        Card::SpecialCard(SpecialCard::Jester),
+                        Card::SpecialCard(SpecialCard::Jester),
+                        Card::SpecialCard(SpecialCard::Jester),
+                    ],
+                    i
+                )
+            );
+        }
+        assert_eq!(
+            1,
+            engine.trick_winner(
+                &[
+                    Card::SpecialCard(SpecialCard::Jester),
+                    Card::SpecialCard(SpecialCard::Jester),
+                    Card::NormalCard(NormalCard {
+                        suit: Suit::Spade,
+                        rank: Rank::Queen
+                    }),
+                ],
+                2
+            )
+        );
+        // Normal cards
+        assert_eq!(
+            2,
+            engine.trick_winner(
+                &[
+                    Card::NormalCard(NormalCard {
+                        suit: Suit::Spade,
+                        rank: Rank::Ace
+                    }),
+                    Card::NormalCard(NormalCard {
+                        suit: Suit::Spade,
+                        rank: Rank::King
+                    }),
+                    Card::NormalCard(NormalCard {
+                        suit: Suit::Spade,
+                        rank: Rank::Queen
+                    }),
+                ],
+                2
+            )
+        );
+
+        engine.trump_suit = Some(Suit::Heart);
+        assert_eq!(
+            0,
+            engine.trick_winner(
+                &[
+                    Card::NormalCard(NormalCard {
+                        suit: Suit::Spade,
+                        rank: Rank::Two
+                    }),
+                    Card::NormalCard(NormalCard {
+                        suit: Suit::Diamond,
+                        rank: Rank::King
+                    }),
+                    Card::NormalCard(NormalCard {
+                        suit: Suit::Club,
+                        rank: Rank::Ace
+                    }),
+                ],
+                0
+            )
+        );
+        assert_eq!(
+            2,
+            engine.trick_winner(
+                &[
+                    Card::NormalCard(NormalCard {
+                        suit: Suit::Spade,
+                        rank: Rank::Ace
+                    }),
+                    Card::NormalCard(NormalCard {
+                        suit: Suit::Heart,
+                        rank: Rank::King
+                    }),
+                    Card::NormalCard(NormalCard {
+                        suit: Suit::Spade,
+                        rank: Rank::Queen
+                    }),
+                ],
+                1
+            )
+        );
+    }
+
+    #[test]
+    fn is_better_card() {
+        // --- Wizards tests --- //
+        assert!(!GameEngine::is_better_card(
+            &Card::SpecialCard(SpecialCard::Wizard),
+            &Card::SpecialCard(SpecialCard::Wizard),
+            Some(Suit::Spade),
+            Some(Suit::Spade)
+        ));
+        assert!(!GameEngine::is_better_card(
+            &Card::SpecialCard(SpecialCard::Wizard),
+            &Card::SpecialCard(SpecialCard::Jester),
+            Some(Suit::Spade),
+            Some(Suit::Spade)
+        ));
+        assert!(GameEngine::is_better_card(
+            &Card::SpecialCard(SpecialCard::Jester),
+            &Card::SpecialCard(SpecialCard::Wizard),
+            Some(Suit::Spade),
+            Some(Suit::Spade)
+        ));
+
+        for trump_suit in Suit::iter() {
+            for lead_suit in Suit::iter() {
+                for suit in Suit::iter() {
+                    for rank in Rank::iter() {
+                        assert!(GameEngine::is_better_card(
+                            &Card::NormalCard(NormalCard { suit, rank }),
+                            &Card::SpecialCard(SpecialCard::Wizard),
+                            Some(trump_suit),
+                            Some(lead_suit)
+                        ));
+                        assert!(!GameEngine::is_better_card(
+                            &Card::SpecialCard(SpecialCard::Wizard),
+                            &Card::NormalCard(NormalCard { suit, rank }),
+                            Some(trump_suit),
+                            Some(lead_suit)
+                        ));
+                    }
+                }
+            }
+        }
+
+        // --- Jester tests --- //
+        assert!(!GameEngine::is_better_card(
+            &Card::SpecialCard(SpecialCard::Jester),
+            &Card::SpecialCard(SpecialCard::Jester),
+            Some(Suit::Spade),
+            Some(Suit::Spade)
+        ));
+
+        for trump_suit in Suit::iter() {
+            for lead_suit in Suit::iter() {
+                for suit in Suit::iter() {
+                    for rank in Rank::iter() {
+                        assert!(!GameEngine::is_better_card(
+                            &Card::NormalCard(NormalCard { suit, rank }),
+                            &Card::SpecialCard(SpecialCard::Jester),
+                            Some(trump_suit),
+                            Some(lead_suit)
+                        ));
+                        assert!(GameEngine::is_better_card(
+                            &Card::SpecialCard(SpecialCard::Jester),
+                            &Card::NormalCard(NormalCard { suit, rank }),
+                            Some(trump_suit),
+                            Some(lead_suit)
+                        ));
+                    }
+                }
+            }
+        }
+
+        // --- Normal card tests --- //
+        // Trump suit wins
+        assert!(GameEngine::is_better_card(
+            &Card::NormalCard(NormalCard {
+                suit: Suit::Heart,
+                rank: Rank::Ace
+            }),
+            &Card::NormalCard(NormalCard {
+                suit: Suit::Spade,
+                rank: Rank::Two
+            }),
+            Some(Suit::Heart),
+            Some(Suit::Spade)
+        ));
+        assert!(!GameEngine::is_better_card(
+            &Card::NormalCard(NormalCard {
+                suit: Suit::Heart,
+                rank: Rank::Ace
+            }),
+            &Card::NormalCard(NormalCard {
+                suit: Suit::Spade,
+                rank: Rank::Two
+            }),
+            Some(Suit::Spade),
+            Some(Suit::Heart),
+        ));
+        // Lead suit wins
+        assert!(GameEngine::is_better_card(
+            &Card::NormalCard(NormalCard {
+                suit: Suit::Heart,
+                rank: Rank::Ace
+            }),
+            &Card::NormalCard(NormalCard {
+                suit: Suit::Spade,
+                rank: Rank::Two
+            }),
+            Some(Suit::Spade),
+            Some(Suit::Diamond)
+        ));
+        assert!(!GameEngine::is_better_card(
+            &Card::NormalCard(NormalCard {
+                suit: Suit::Heart,
+                rank: Rank::Ace
+            }),
+            &Card::NormalCard(NormalCard {
+                suit: Suit::Spade,
+                rank: Rank::Two
+            }),
+            Some(Suit::Heart),
+            Some(Suit::Diamond)
+        ));
+        // High card of same suit wins
+        assert!(GameEngine::is_better_card(
+            &Card::NormalCard(NormalCard {
+                suit: Suit::Heart,
+                rank: Rank::Two
+            }),
+            &Card::NormalCard(NormalCard {
+                suit: Suit::Heart,
+                rank: Rank::Ace
+            }),
+            Some(Suit::Club),
+            Some(Suit::Diamond)
+        ));
+        assert!(!GameEngine::is_better_card(
+            &Card::NormalCard(NormalCard {
+                suit: Suit::Heart,
+                rank: Rank::Ace
+            }),
+            &Card::NormalCard(NormalCard {
+                suit: Suit::Heart,
+                rank: Rank::Two
+            }),
+            Some(Suit::Club),
+            Some(Suit::Diamond)
+        ));
+        // First card of none lead nor trump suit wins
+        assert!(!GameEngine::is_better_card(
+            &Card::NormalCard(NormalCard {
+                suit: Suit::Heart,
+                rank: Rank::Two
+            }),
+            &Card::NormalCard(NormalCard {
+                suit: Suit::Spade,
+                rank: Rank::Ace
+            }),
+            Some(Suit::Club),
+            Some(Suit::Diamond)
+        ));
+        assert!(!GameEngine::is_better_card(
+            &Card::NormalCard(NormalCard {
+                suit: Suit::Heart,
+                rank: Rank::Ace
+            }),
+            &Card::NormalCard(NormalCard {
+                suit: Suit::Spade,
+                rank: Rank::Two
+            }),
+            Some(Suit::Club),
+            Some(Suit::Diamond)
+        ));
+    }
+}
@@ -0,0 +1,233 @@
+use crate::cards::card::Card;
+use crate::cards::suit::Suit;
+use crate::game::rules::GameRules;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// Something that happened during a game
+///
+/// The `GameEngine` appends an event for every step of the game. Replaying the events through
+/// `GameEngine::replay` rebuilds the game as it was after the last event.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event")]
+pub enum GameEvent {
+    /// Seed is hidden in redacted events
+    GameStarted {
+        num_players: usize,
+        seed: Option<u64>,
+        rules: GameRules,
+    },
+    /// Hands dealt at the start of a round, indexed by player. Hands are empty in redacted events.
+    CardsDealt {
+        round: u8,
+        dealer: usize,
+        hands: Vec<Vec<Card>>,
+    },
+    /// Card turned up after dealing. There is none when every card was dealt.
+    TrumpRevealed {
+        card: Option<Card>,
+        trump_suit: Option<Suit>,
+    },
+    /// Player dealt the Werewolf swapped it for the trump card
+    WerewolfSwapped {
+        player: usize,
+        card: Card,
+    },
+    /// Dealer picked trump after a Wizard was revealed, or the Werewolf's holder picked it
+    TrumpChosen {
+        player: usize,
+        trump: Suit,
+    },
+    BidPlaced {
+        player: usize,
+        bid: u8,
+    },
+    CardPlayed {
+        player: usize,
+        card: Card,
+    },
+    /// Trick was completed. Cards are in the order they were played.
+    TrickWon {
+        player: usize,
+        cards: Vec<Card>,
+    },
+    /// Nobody won the trick since a Bomb was played in it
+    TrickBombed {
+        next_leader: usize,
+        cards: Vec<Card>,
+    },
+    /// Trick winner raised or lowered their bid because a Cloud was played in the trick
+    BidAdjusted {
+        player: usize,
+        adjustment: i8,
+    },
+    /// Card passed to the player on the left after a Juggler was played. Card is hidden in
+    /// redacted events.
+    CardPassed {
+        player: usize,
+        card: Option<Card>,
+    },
+    /// Scores of every player after the round
+    RoundScored {
+        round: u8,
+        scores: Vec<i32>,
+    },
+    /// Final score of every player
+    GameEnded {
+        scores: Vec<i32>,
+    },
+}
+
+impl GameEvent {
+    /// The event as seen by someone who may not look at any hand
+    ///
+    /// The seed, dealt hands and passed cards are hidden. Redacted events can not be replayed.
+    pub fn redacted(&self) -> GameEvent {
+        match self {
+            GameEvent::GameStarted {
+                num_players, rules, ..
+            } => GameEvent::GameStarted {
+                num_players: *num_players,
+                seed: None,
+                rules: rules.clone(),
+            },
+            GameEvent::CardsDealt {
+                round,
+                dealer,
+                hands,
+            } => GameEvent::CardsDealt {
+                round: *round,
+                dealer: *dealer,
+                hands: vec![Vec::new(); hands.len()],
+            },
+            GameEvent::CardPassed { player, .. } => GameEvent::CardPassed {
+                player: *player,
+                card: None,
+            },
+            event => event.clone(),
+        }
+    }
+}
+
+/// Write events to a file, one JSON object per line
+pub fn write_event_log(path: &Path, events: &[GameEvent]) -> Result<()> {
+    let file = File::create(path)
+        .with_context(|| format!("Failed to create event log {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    for event in events.iter() {
+        serde_json::to_writer(&mut writer, event)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Read events written by `write_event_log`
+pub fn read_event_log(path: &Path) -> Result<Vec<GameEvent>> {
+    let file =
+        File::open(path).with_context(|| format!("Failed to open event log {}", path.display()))?;
+
+    let mut events = Vec::new();
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        events.push(
+            serde_json::from_str(&line)
+                .with_context(|| format!("Invalid event on line {}", i + 1))?,
+        );
+    }
+
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::special_card::SpecialCard;
+    use serde_json::json;
+
+    #[test]
+    fn event_wire_format() {
+        let event = GameEvent::CardPlayed {
+            player: 2,
+            card: Card::SpecialCard(SpecialCard::Wizard),
+        };
+
+        assert_eq!(
+            json!({"event": "CardPlayed", "player": 2, "card": "Wizard"}),
+            serde_json::to_value(&event).unwrap()
+        );
+    }
+
+    #[test]
+    fn redacted_events_hide_hands() {
+        let wizard = Card::SpecialCard(SpecialCard::Wizard);
+        let dealt = GameEvent::CardsDealt {
+            round: 1,
+            dealer: 0,
+            hands: vec![vec![wizard], vec![wizard]],
+        };
+        assert_eq!(
+            GameEvent::CardsDealt {
+                round: 1,
+                dealer: 0,
+                hands: vec![Vec::new(), Vec::new()],
+            },
+            dealt.redacted()
+        );
+
+        let passed = GameEvent::CardPassed {
+            player: 1,
+            card: Some(wizard),
+        };
+        assert_eq!(
+            json!({"event": "CardPassed", "player": 1, "card": "Wizard"}),
+            serde_json::to_value(&passed).unwrap()
+        );
+        assert_eq!(
+            GameEvent::CardPassed {
+                player: 1,
+                card: None
+            },
+            passed.redacted()
+        );
+
+        let played = GameEvent::CardPlayed {
+            player: 1,
+            card: wizard,
+        };
+        assert_eq!(played, played.redacted());
+    }
+
+    #[test]
+    fn write_and_read_event_log() {
+        let events = vec![
+            GameEvent::GameStarted {
+                num_players: 3,
+                seed: Some(42),
+                rules: GameRules::default(),
+            },
+            GameEvent::TrumpRevealed {
+                card: None,
+                trump_suit: None,
+            },
+            GameEvent::BidPlaced { player: 1, bid: 0 },
+        ];
+        let path = std::env::temp_dir().join(format!(
+            "wizard_event_log_test_{}.jsonl",
+            std::process::id()
+        ));
+
+        write_event_log(&path, &events).unwrap();
+        let read = read_event_log(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(events, read);
+    }
+}
@@ -1 +1,8 @@
+pub mod engine;
+pub mod event;
+pub mod rule_violation;
+pub mod rules;
+pub mod scoring;
+pub mod state;
+pub mod timeouts;
 pub mod wizard;
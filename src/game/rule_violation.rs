@@ -0,0 +1,63 @@
+use crate::cards::card::Card;
+use std::fmt;
+
+/// Input rejected by the `GameEngine` because it breaks the rules of the game
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RuleViolation {
+    /// Input was given by a player whose turn it is not, or for the wrong phase of the round
+    NotYourTurn { player: usize },
+    /// Bid is larger than the number of cards dealt this round
+    InvalidBid { bid: u8, hand_size: u8 },
+    /// Bid would make the total of all bids equal the number of cards dealt
+    ForbiddenBid { bid: u8 },
+    /// Bid can only be raised or lowered by one, and not below zero
+    InvalidBidAdjustment { bid: u8, adjustment: i8 },
+    /// Juggler or Cloud was played without choosing its suit
+    SuitNotChosen { card: Card },
+    /// Played card is not in the player's hand
+    CardNotInHand { card: Card },
+    /// Played card is in the player's hand but must not be played. E.g. not following suit
+    CardNotPlayable {
+        card: Card,
+        playable_cards: Vec<Card>,
+    },
+}
+
+impl fmt::Display for RuleViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleViolation::NotYourTurn { player } => {
+                write!(f, "It is not player {}'s turn for this action", player)
+            }
+            RuleViolation::InvalidBid { bid, hand_size } => {
+                write!(f, "Bid {} is not between 0 and {}", bid, hand_size)
+            }
+            RuleViolation::ForbiddenBid { bid } => write!(
+                f,
+                "Bid {} is not allowed. Total bids may not equal the number of cards dealt",
+                bid
+            ),
+            RuleViolation::InvalidBidAdjustment { bid, adjustment } => write!(
+                f,
+                "Bid {} cannot be adjusted by {}. Adjust by 1 or -1 without going below 0",
+                bid, adjustment
+            ),
+            RuleViolation::SuitNotChosen { card } => {
+                write!(f, "A suit must be chosen to play card {:?}", card)
+            }
+            RuleViolation::CardNotInHand { card } => {
+                write!(f, "Played card is not in player's hand. Card: {:?}", card)
+            }
+            RuleViolation::CardNotPlayable {
+                card,
+                playable_cards,
+            } => write!(
+                f,
+                "Played card is not playable. Card: {:?}; Playable cards: {:?}",
+                card, playable_cards
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RuleViolation {}
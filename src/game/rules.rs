@@ -0,0 +1,145 @@
+use crate::cards::deck::DeckSpec;
+use crate::cards::special_card::SpecialCard;
+use crate::game::scoring::ScoringScheme;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+/// Optional rules a game can be played with
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameRules {
+    /// The last bidder may not make the total of all bids equal the number of cards dealt
+    pub restricted_bidding: bool,
+    /// Every player bids at once without seeing the other bids. The bids are revealed together.
+    pub blind_bidding: bool,
+    /// Anniversary edition cards shuffled into the deck, e.g. `["Bomb", "Dragon", "Fairy"]`
+    pub special_cards: Vec<SpecialCard>,
+    /// Cards the deck is built from, before adding `special_cards`. When not set, the deck suited
+    /// to the number of players.
+    pub deck: Option<DeckSpec>,
+    /// Number of cards dealt in each round
+    pub rounds: RoundSchedule,
+    /// How rounds are scored, e.g. `[rules.scoring]` with `scheme = "DoubledLastRound"`
+    pub scoring: ScoringScheme,
+}
+
+impl GameRules {
+    /// Standard rules with the deck for the number of players set explicitly
+    pub fn for_table(num_players: usize) -> GameRules {
+        GameRules {
+            deck: Some(DeckSpec::for_table(num_players)),
+            ..Default::default()
+        }
+    }
+
+    /// Deck a game of `num_players` is played with: the configured one, or else the one suited to
+    /// the number of players
+    pub fn deck_for(&self, num_players: usize) -> DeckSpec {
+        self.deck
+            .clone()
+            .unwrap_or_else(|| DeckSpec::for_table(num_players))
+    }
+}
+
+/// Number of cards dealt in each round of a game
+///
+/// Serialized with a "schedule" field naming the variant, e.g. `{ schedule = "Short", rounds = 5 }`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "schedule", deny_unknown_fields)]
+pub enum RoundSchedule {
+    /// One card more each round until the deck runs out
+    #[default]
+    Full,
+    /// One card more each round for `rounds` rounds
+    Short { rounds: u8 },
+    /// One card more each round until the deck runs out, then one card less back down to one
+    UpAndDown,
+    /// Exactly these hand sizes
+    Custom { rounds: Vec<u8> },
+}
+
+impl RoundSchedule {
+    /// Hand size of each round given the largest hand the deck can deal
+    ///
+    /// # Errors
+    /// If the schedule has no rounds, or a round is empty or larger than `max_hand_size`
+    pub fn hand_sizes(&self, max_hand_size: u8) -> Result<Vec<u8>> {
+        let hand_sizes: Vec<u8> = match self {
+            RoundSchedule::Full => (1..=max_hand_size).collect(),
+            RoundSchedule::Short { rounds } => (1..=*rounds).collect(),
+            RoundSchedule::UpAndDown => (1..=max_hand_size)
+                .chain((1..max_hand_size).rev())
+                .collect(),
+            RoundSchedule::Custom { rounds } => rounds.clone(),
+        };
+
+        if hand_sizes.is_empty() {
+            bail!("Round schedule has no rounds. Schedule: {:?}", self);
+        }
+        if let Some(size) = hand_sizes
+            .iter()
+            .find(|size| **size == 0 || **size > max_hand_size)
+        {
+            bail!(
+                "Rounds must deal between 1 and {} cards. Round with {} cards in schedule: {:?}",
+                max_hand_size,
+                size,
+                self
+            );
+        }
+
+        Ok(hand_sizes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hand_sizes() {
+        assert_eq!(vec![1, 2, 3], RoundSchedule::Full.hand_sizes(3).unwrap());
+        assert_eq!(
+            vec![1, 2],
+            RoundSchedule::Short { rounds: 2 }.hand_sizes(3).unwrap()
+        );
+        assert_eq!(
+            vec![1, 2, 3, 2, 1],
+            RoundSchedule::UpAndDown.hand_sizes(3).unwrap()
+        );
+        assert_eq!(
+            vec![3, 3],
+            RoundSchedule::Custom { rounds: vec![3, 3] }
+                .hand_sizes(3)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn impossible_schedules_are_rejected() {
+        assert!(RoundSchedule::Short { rounds: 4 }.hand_sizes(3).is_err());
+        assert!(RoundSchedule::Short { rounds: 0 }.hand_sizes(3).is_err());
+        assert!(RoundSchedule::Custom { rounds: vec![1, 0] }
+            .hand_sizes(3)
+            .is_err());
+        assert!(RoundSchedule::Full.hand_sizes(0).is_err());
+    }
+
+    #[test]
+    fn rules_from_toml() {
+        let rules: GameRules = toml::from_str(
+            "[deck]\nwizards = 2\nremoved_ranks = [\"Two\"]\n\n[rounds]\nschedule = \"Custom\"\nrounds = [1, 3, 1]\n",
+        )
+        .unwrap();
+
+        let deck = rules.deck.unwrap();
+        assert_eq!(2, deck.wizards);
+        assert_eq!(4, deck.jesters);
+        assert_eq!(
+            RoundSchedule::Custom {
+                rounds: vec![1, 3, 1]
+            },
+            rules.rounds
+        );
+    }
+}
@@ -0,0 +1,282 @@
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// How a single player did in a round
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RoundResult {
+    pub round: u8,
+    pub num_rounds: u8,
+    pub bid: u8,
+    pub tricks_taken: u8,
+}
+
+impl RoundResult {
+    pub fn made_bid(&self) -> bool {
+        self.bid == self.tricks_taken
+    }
+
+    /// Number of tricks the bid was missed by
+    pub fn missed_by(&self) -> i32 {
+        (self.bid as i32 - self.tricks_taken as i32).abs()
+    }
+
+    pub fn is_last_round(&self) -> bool {
+        self.round == self.num_rounds
+    }
+}
+
+/// Largest penalty or bonus a scoring scheme can be set to
+pub const MAX_POINTS: i16 = 1000;
+
+/// Points a player earns or loses at the end of a round
+pub trait ScoringRule: Debug + Send + Sync {
+    fn score(&self, result: &RoundResult) -> i32;
+}
+
+/// 20 points plus 10 per trick for making the bid, otherwise -10 per trick the bid was missed by
+#[derive(Copy, Clone, Debug, Default)]
+pub struct StandardScoring;
+
+impl ScoringRule for StandardScoring {
+    fn score(&self, result: &RoundResult) -> i32 {
+        if result.made_bid() {
+            20 + 10 * result.tricks_taken as i32
+        } else {
+            -10 * result.missed_by()
+        }
+    }
+}
+
+/// Standard points for making the bid, but a missed bid always loses `penalty` points
+#[derive(Copy, Clone, Debug)]
+pub struct FlatPenaltyScoring {
+    pub penalty: i16,
+}
+
+impl ScoringRule for FlatPenaltyScoring {
+    fn score(&self, result: &RoundResult) -> i32 {
+        if result.made_bid() {
+            StandardScoring.score(result)
+        } else {
+            -(self.penalty as i32)
+        }
+    }
+}
+
+/// Standard scoring with `bonus` extra points for making a bid of zero from `from_round` on
+#[derive(Copy, Clone, Debug)]
+pub struct ZeroBidBonusScoring {
+    pub from_round: u8,
+    pub bonus: i16,
+}
+
+impl ScoringRule for ZeroBidBonusScoring {
+    fn score(&self, result: &RoundResult) -> i32 {
+        let score = StandardScoring.score(result);
+        if result.bid == 0 && result.made_bid() && result.round >= self.from_round {
+            score + self.bonus as i32
+        } else {
+            score
+        }
+    }
+}
+
+/// Standard scoring where points gained or lost in the last round count double
+#[derive(Copy, Clone, Debug, Default)]
+pub struct DoubledLastRoundScoring;
+
+impl ScoringRule for DoubledLastRoundScoring {
+    fn score(&self, result: &RoundResult) -> i32 {
+        let score = StandardScoring.score(result);
+        if result.is_last_round() {
+            score * 2
+        } else {
+            score
+        }
+    }
+}
+
+/// Scoring rule a game is played with
+///
+/// Serialized with a "scheme" field naming the variant alongside its settings, e.g.
+/// `{ scheme = "FlatPenalty", penalty = 20 }`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "scheme", deny_unknown_fields)]
+pub enum ScoringScheme {
+    #[default]
+    Standard,
+    FlatPenalty {
+        penalty: i16,
+    },
+    ZeroBidBonus {
+        from_round: u8,
+        bonus: i16,
+    },
+    DoubledLastRound,
+    /// Rule added with `register_scoring_rule`
+    Custom {
+        name: String,
+    },
+}
+
+impl ScoringScheme {
+    /// Rule that scores rounds for this scheme
+    ///
+    /// # Errors
+    /// If a penalty or bonus is not between 0 and `MAX_POINTS`, or a custom scheme has not been
+    /// registered
+    pub fn rule(&self) -> Result<Arc<dyn ScoringRule>> {
+        if let ScoringScheme::FlatPenalty { penalty: points }
+        | ScoringScheme::ZeroBidBonus { bonus: points, .. } = self
+        {
+            if !(0..=MAX_POINTS).contains(points) {
+                bail!(
+                    "Penalties and bonuses must be between 0 and {} points. Scheme: {:?}",
+                    MAX_POINTS,
+                    self
+                );
+            }
+        }
+
+        Ok(match self {
+            ScoringScheme::Standard => Arc::new(StandardScoring),
+            ScoringScheme::FlatPenalty { penalty } => {
+                Arc::new(FlatPenaltyScoring { penalty: *penalty })
+            }
+            ScoringScheme::ZeroBidBonus { from_round, bonus } => Arc::new(ZeroBidBonusScoring {
+                from_round: *from_round,
+                bonus: *bonus,
+            }),
+            ScoringScheme::DoubledLastRound => Arc::new(DoubledLastRoundScoring),
+            ScoringScheme::Custom { name } => custom_rules()
+                .read()
+                .unwrap()
+                .get(name)
+                .cloned()
+                .ok_or_else(|| anyhow!("No scoring rule registered as {:?}", name))?,
+        })
+    }
+}
+
+fn custom_rules() -> &'static RwLock<HashMap<String, Arc<dyn ScoringRule>>> {
+    static CUSTOM_RULES: OnceLock<RwLock<HashMap<String, Arc<dyn ScoringRule>>>> = OnceLock::new();
+    CUSTOM_RULES.get_or_init(Default::default)
+}
+
+/// Make a scoring rule available to games as `ScoringScheme::Custom { name }`
+///
+/// Registering a rule under a name that is already taken replaces the old rule.
+pub fn register_scoring_rule(name: &str, rule: impl ScoringRule + 'static) {
+    custom_rules()
+        .write()
+        .unwrap()
+        .insert(name.to_string(), Arc::new(rule));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(round: u8, bid: u8, tricks_taken: u8) -> RoundResult {
+        RoundResult {
+            round,
+            num_rounds: 20,
+            bid,
+            tricks_taken,
+        }
+    }
+
+    #[test]
+    fn standard_scoring() {
+        assert_eq!(20, StandardScoring.score(&result(1, 0, 0)));
+        assert_eq!(50, StandardScoring.score(&result(5, 3, 3)));
+        assert_eq!(-20, StandardScoring.score(&result(5, 1, 3)));
+    }
+
+    #[test]
+    fn house_variants() {
+        let flat = FlatPenaltyScoring { penalty: 15 };
+        assert_eq!(40, flat.score(&result(5, 2, 2)));
+        assert_eq!(-15, flat.score(&result(5, 0, 4)));
+
+        let zero_bonus = ZeroBidBonusScoring {
+            from_round: 10,
+            bonus: 30,
+        };
+        assert_eq!(20, zero_bonus.score(&result(9, 0, 0)));
+        assert_eq!(50, zero_bonus.score(&result(10, 0, 0)));
+        assert_eq!(-10, zero_bonus.score(&result(10, 0, 1)));
+
+        assert_eq!(30, DoubledLastRoundScoring.score(&result(19, 1, 1)));
+        assert_eq!(60, DoubledLastRoundScoring.score(&result(20, 1, 1)));
+        assert_eq!(-40, DoubledLastRoundScoring.score(&result(20, 0, 2)));
+    }
+
+    #[derive(Debug)]
+    struct TricksOnly;
+
+    impl ScoringRule for TricksOnly {
+        fn score(&self, result: &RoundResult) -> i32 {
+            result.tricks_taken as i32
+        }
+    }
+
+    #[test]
+    fn custom_rules_must_be_registered() {
+        let scheme = ScoringScheme::Custom {
+            name: "tricks_only".to_string(),
+        };
+        assert!(scheme.rule().is_err());
+
+        register_scoring_rule("tricks_only", TricksOnly);
+        assert_eq!(3, scheme.rule().unwrap().score(&result(5, 0, 3)));
+    }
+
+    #[test]
+    fn points_out_of_range_are_rejected() {
+        for points in [-1, MAX_POINTS + 1, i16::MIN, i16::MAX] {
+            assert!(ScoringScheme::FlatPenalty { penalty: points }
+                .rule()
+                .is_err());
+            assert!(ScoringScheme::ZeroBidBonus {
+                from_round: 1,
+                bonus: points
+            }
+            .rule()
+            .is_err());
+        }
+
+        // The largest scores a round can give fit with room to spare
+        let bonus = ScoringScheme::ZeroBidBonus {
+            from_round: 1,
+            bonus: MAX_POINTS,
+        };
+        assert_eq!(1020, bonus.rule().unwrap().score(&result(1, 0, 0)));
+        let last_round = RoundResult {
+            round: u8::MAX,
+            num_rounds: u8::MAX,
+            bid: u8::MAX,
+            tricks_taken: u8::MAX,
+        };
+        assert_eq!(5140, DoubledLastRoundScoring.score(&last_round));
+        assert_eq!(
+            -5100,
+            DoubledLastRoundScoring.score(&RoundResult {
+                bid: 0,
+                ..last_round
+            })
+        );
+    }
+
+    #[test]
+    fn scheme_from_toml() {
+        assert_eq!(
+            ScoringScheme::FlatPenalty { penalty: 20 },
+            toml::from_str("scheme = \"FlatPenalty\"\npenalty = 20").unwrap()
+        );
+        assert!(toml::from_str::<ScoringScheme>("scheme = \"Unknown\"").is_err());
+    }
+}
@@ -0,0 +1,40 @@
+use crate::cards::card::Card;
+use crate::cards::suit::Suit;
+use serde::{Deserialize, Serialize};
+
+/// Public state of a game that every player is allowed to see
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameState {
+    pub round: u8,
+    pub num_rounds: u8,
+    /// Cards dealt to each player this round
+    pub hand_size: u8,
+    /// Seed the game was dealt from. Only revealed once the game is over.
+    pub seed: Option<u64>,
+    /// Player dealing this round
+    pub dealer: usize,
+    pub trump_suit: Option<Suit>,
+    pub player_count: usize,
+    pub players: Vec<PlayerState>,
+    /// Cards played so far in the current trick
+    pub played_cards: Vec<Card>,
+    /// Player who leads the current trick
+    pub current_leader: usize,
+    pub leading_suit: Option<Suit>,
+}
+
+/// Public state of a single player
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerState {
+    pub name: String,
+    pub score: i32,
+    pub bid: Option<u8>,
+    pub tricks_taken: u8,
+}
+
+/// Bid a player has made this round
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlacedBid {
+    pub player: usize,
+    pub bid: u8,
+}
@@ -0,0 +1,64 @@
+use crate::game::engine::Turn;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How long a player has to make each kind of decision, in milliseconds
+///
+/// A decision without a limit is waited on forever. When a limit passes the server makes a legal
+/// move for the player and tells the table.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TurnTimeouts {
+    pub choose_trump_ms: Option<u64>,
+    pub bid_ms: Option<u64>,
+    pub play_card_ms: Option<u64>,
+    pub adjust_bid_ms: Option<u64>,
+    pub pass_card_ms: Option<u64>,
+}
+
+impl TurnTimeouts {
+    /// The same limit for every decision
+    pub fn all(ms: u64) -> TurnTimeouts {
+        TurnTimeouts {
+            choose_trump_ms: Some(ms),
+            bid_ms: Some(ms),
+            play_card_ms: Some(ms),
+            adjust_bid_ms: Some(ms),
+            pass_card_ms: Some(ms),
+        }
+    }
+
+    /// Time limit for the decision `turn` asks for
+    pub fn limit(&self, turn: Turn) -> Option<Duration> {
+        let ms = match turn {
+            Turn::ChooseTrump(_) => self.choose_trump_ms,
+            Turn::Bid(_) | Turn::BlindBid => self.bid_ms,
+            Turn::PlayCard(_) => self.play_card_ms,
+            Turn::AdjustBid(_) => self.adjust_bid_ms,
+            Turn::PassCard(_) => self.pass_card_ms,
+            Turn::GameOver => None,
+        };
+        ms.map(Duration::from_millis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_from_toml() {
+        let timeouts: TurnTimeouts = toml::from_str("bid_ms = 500\nplay_card_ms = 1000").unwrap();
+
+        assert_eq!(
+            Some(Duration::from_millis(500)),
+            timeouts.limit(Turn::BlindBid)
+        );
+        assert_eq!(
+            Some(Duration::from_secs(1)),
+            timeouts.limit(Turn::PlayCard(2))
+        );
+        assert_eq!(None, timeouts.limit(Turn::ChooseTrump(0)));
+        assert!(toml::from_str::<TurnTimeouts>("bid = 500").is_err());
+    }
+}
@@ -1,744 +1,512 @@
 use crate::cards::card::Card;
-use crate::cards::deck::Deck;
 use crate::cards::special_card::SpecialCard;
 use crate::cards::suit::Suit;
+use crate::game::engine::{GameEngine, Turn};
+use crate::game::event::GameEvent;
+use crate::game::rule_violation::RuleViolation;
+use crate::game::rules::GameRules;
+use crate::game::timeouts::TurnTimeouts;
+use crate::network::action::{ErrorMessage, ErrorReason};
+use crate::network::connection::Connection;
+use crate::network::network::{Disconnected, TimedOut};
 use crate::players::player::Player;
-use anyhow::{bail, Result};
-use serde_json::{Map, Value};
-use std::net::TcpStream;
+use crate::players::spectator::Spectator;
+use anyhow::{bail, Error, Result};
+use std::cmp::Reverse;
+use std::fmt::Debug;
+use std::sync::mpsc::Receiver;
+use std::thread;
+use std::time::{Duration, Instant};
+use strum::IntoEnumIterator;
+
+/// Number of times a player is asked for a decision before the server decides for them
+const MAX_ATTEMPTS: usize = 3;
+
+/// Client that rejoined a seat with its session token
+pub struct Reconnection {
+    pub seat: usize,
+    pub connection: Connection,
+}
 
+/// Networked game of Wizard
+///
+/// Drives a `GameEngine` by asking each connected `Player` for its decisions.
 pub struct WizardGame {
+    engine: GameEngine,
     players: Vec<Player>,
-    starting_player: usize,
-    round: u8,
-    trump_suit: Option<Suit>,
+    /// Rejoined clients and how long to wait for a dropped player to rejoin
+    reconnections: Option<(Receiver<Reconnection>, Duration)>,
+    timeouts: TurnTimeouts,
+    spectators: Vec<Spectator>,
+    /// Spectators who arrived and have not been sent the game so far yet
+    spectator_arrivals: Option<Receiver<Spectator>>,
+    /// Number of events already sent to players and spectators
+    events_sent: usize,
 }
 
 impl WizardGame {
+    /// Create a game for the connected players
+    ///
+    /// The deals are shuffled from `seed` when one is given, otherwise from a random seed
     pub fn new(
         num_players: usize,
-        client_listeners: Vec<TcpStream>,
-        client_writers: Vec<TcpStream>,
+        players: Vec<Player>,
+        seed: Option<u64>,
+        rules: GameRules,
     ) -> Result<WizardGame> {
-        if num_players < 3 {
-            bail!(
-                "Not enough players. Minimum of 3 players required. Players requested: {}",
-                num_players
-            );
-        } else if num_players > 6 {
+        let seed = seed.unwrap_or_else(rand::random);
+        let mut engine = GameEngine::with_rules(num_players, seed, rules)?;
+        println!("Game seed: {}", engine.seed());
+
+        if num_players != players.len() {
             bail!(
-                "Too many players. Maximum of 6 players. Players requested: {}",
-                num_players
+                "Players does not equal number of connected players. Players: {}, Connected: {}",
+                num_players,
+                players.len()
             );
         }
-        if num_players != client_listeners.len() || num_players != client_writers.len() {
-            bail!("Players does not equal number of client listeners or writers. Players: {}, Listeners: {}, Writers: {}", num_players, client_listeners.len(), client_writers.len());
-        }
 
-        let mut players = Vec::new();
-        for (client_listener, client_writer) in
-            client_listeners.into_iter().zip(client_writers.into_iter())
-        {
-            players.push(Player::new(client_listener, client_writer));
+        for (i, player) in players.iter().enumerate() {
+            engine.set_name(i, player.name());
         }
 
         Ok(WizardGame {
+            engine,
             players,
-            starting_player: 0,
-            round: 0,
-            trump_suit: None,
+            reconnections: None,
+            timeouts: TurnTimeouts::default(),
+            spectators: Vec::new(),
+            spectator_arrivals: None,
+            events_sent: 0,
         })
     }
 
-    pub fn play_game(&mut self) {
-        let num_rounds = 60 / self.players.len();
+    /// Let clients watch the game
+    ///
+    /// Spectators arriving on `receiver` are sent every event so far, then each new event as
+    /// the game goes on.
+    pub fn accept_spectators(&mut self, receiver: Receiver<Spectator>) {
+        self.spectator_arrivals = Some(receiver);
+    }
+
+    /// Limit how long players have for each decision
+    pub fn set_turn_timeouts(&mut self, timeouts: TurnTimeouts) {
+        self.timeouts = timeouts;
+    }
+
+    /// Let dropped players rejoin their seat
+    ///
+    /// When a player's connection drops on their turn, the game pauses for up to `timeout` until a
+    /// `Reconnection` for their seat arrives on `receiver`. Without this a dropped player ends
+    /// the game.
+    pub fn accept_reconnections(&mut self, receiver: Receiver<Reconnection>, timeout: Duration) {
+        self.reconnections = Some((receiver, timeout));
+    }
+
+    /// Everything that has happened in the game so far
+    pub fn events(&self) -> &[GameEvent] {
+        self.engine.events()
+    }
 
+    pub fn play_game(&mut self) -> Result<()> {
         // Tell players we are starting the game
-        let state = self.game_state();
+        let state = self.engine.game_state();
         for player in self.players.iter_mut() {
             player.start_game(&state);
         }
 
-        for _ in 0..num_rounds {
-            self.perform_round().unwrap();
+        loop {
+            self.send_events();
+            match self.engine.turn() {
+                Turn::ChooseTrump(i) => self.prompt(
+                    i,
+                    |player, engine| player.choose_trump(engine.hand(i), &engine.game_state()),
+                    |engine, suit| engine.choose_trump(i, suit),
+                    |engine| fallback_suit(engine.hand(i)),
+                )?,
+                Turn::Bid(i) => self.prompt(
+                    i,
+                    |player, engine| {
+                        player.bid(
+                            i,
+                            engine.hand(i),
+                            &engine.bids(),
+                            engine.forbidden_bid(),
+                            &engine.game_state(),
+                        )
+                    },
+                    |engine, bid| engine.bid(i, bid),
+                    |engine| match engine.forbidden_bid() {
+                        Some(0) => 1,
+                        _ => 0,
+                    },
+                )?,
+                Turn::PlayCard(i) => self.prompt(
+                    i,
+                    |player, engine| {
+                        player.play_card(
+                            engine.hand(i),
+                            &engine.playable_cards(i),
+                            &engine.game_state(),
+                        )
+                    },
+                    |engine, card| engine.play_card(i, card).map(|_| ()),
+                    |engine| {
+                        let suit = fallback_suit(engine.hand(i));
+                        weakest_card(&engine.playable_cards(i)).with_chosen_suit(suit)
+                    },
+                )?,
+                Turn::BlindBid => self.collect_blind_bids()?,
+                Turn::AdjustBid(i) => self.prompt(
+                    i,
+                    |player, engine| {
+                        let bid = engine.seats()[i].bid.unwrap_or(0);
+                        player.adjust_bid(engine.hand(i), bid, &engine.game_state())
+                    },
+                    |engine, adjustment| engine.adjust_bid(i, adjustment),
+                    |_| 1,
+                )?,
+                Turn::PassCard(i) => self.prompt(
+                    i,
+                    |player, engine| player.pass_card(engine.hand(i), &engine.game_state()),
+                    |engine, card| engine.pass_card(i, card),
+                    |engine| weakest_card(engine.hand(i)),
+                )?,
+                Turn::GameOver => break,
+            }
         }
 
         // Tell players the game has ended
-        let state = self.game_state();
+        self.resume_reconnected();
+        self.send_events();
+        let state = self.engine.game_state();
         for player in self.players.iter_mut() {
             player.end_game(&state);
         }
-    }
-    fn perform_round(&mut self) -> Result<()> {
-        self.round += 1;
-        let mut deck = Deck::new();
-        // Deal the cards and set the trump suit
-        self.deal(&mut deck)?;
-        // Reveal trump - Allow player to choose if trump is a wizard
-        self.reveal_trump(&mut deck);
-        // Each player bids
-        self.bid();
-        // Play all cards
-        self.play_cards();
-        // Calculate score
-        self.update_player_scores();
+        for spectator in self.spectators.iter_mut() {
+            spectator.end_game(&state);
+        }
 
         Ok(())
     }
 
-    /// Deal the shuffled deck of cards to each player based on the round number
-    fn deal(&mut self, deck: &mut Deck) -> Result<()> {
-        for player in self.players.iter_mut() {
-            player.cards = deck.deal(self.round as usize)?;
+    /// Ask a player for a decision until the engine accepts it
+    ///
+    /// Each rejected response is answered with an `Action::Error` and the player is asked again.
+    /// After `MAX_ATTEMPTS` rejected responses the `fallback` decision is made for the player.
+    /// The fallback is also made, and the table told about it, if the player runs out of time.
+    /// A player whose connection drops is asked again once they rejoin, with their time reset.
+    ///
+    /// # Arguments
+    ///
+    /// * `i` - Player making the decision
+    /// * `ask` - Request the decision from the player
+    /// * `apply` - Apply the decision to the engine
+    /// * `fallback` - Legal decision to make if the player never gives one
+    fn prompt<T: Debug>(
+        &mut self,
+        i: usize,
+        ask: impl Fn(&mut Player, &GameEngine) -> Result<T>,
+        apply: impl Fn(&mut GameEngine, T) -> Result<()>,
+        fallback: impl Fn(&GameEngine) -> T,
+    ) -> Result<()> {
+        let limit = self.timeouts.limit(self.engine.turn());
+        self.players[i].set_deadline(limit.map(|limit| Instant::now() + limit));
+
+        let mut attempts = 0;
+        while attempts < MAX_ATTEMPTS {
+            self.resume_reconnected();
+            let error = match ask(&mut self.players[i], &self.engine) {
+                Ok(decision) => match apply(&mut self.engine, decision) {
+                    Ok(()) => return Ok(()),
+                    Err(e) => WizardGame::rejection(e)?,
+                },
+                Err(e) if e.is::<Disconnected>() => {
+                    self.wait_for_reconnection(i)?;
+                    self.players[i].set_deadline(limit.map(|limit| Instant::now() + limit));
+                    continue;
+                }
+                Err(e) if e.is::<TimedOut>() => {
+                    let decision = fallback(&self.engine);
+                    self.announce_timeout(i, &decision);
+                    return apply(&mut self.engine, decision);
+                }
+                Err(e) => ErrorMessage {
+                    reason: ErrorReason::UnexpectedAction,
+                    msg: e.to_string(),
+                },
+            };
+
+            attempts += 1;
+            eprintln!("Player {} response rejected: {}", i, error.msg);
+            self.players[i].error(error);
         }
 
-        Ok(())
+        eprintln!(
+            "Player {} failed to respond {} times. Deciding for them.",
+            i, MAX_ATTEMPTS
+        );
+        let decision = fallback(&self.engine);
+        apply(&mut self.engine, decision)
     }
 
-    /// Reveal trump. If the trump is a wizard, the player before the starting_player
-    /// gets to choose the trump suit
-    fn reveal_trump(&mut self, deck: &mut Deck) {
-        let top_card = deck.deal(1);
-
-        match top_card {
-            Ok(card) => {
-                let trump_card = &card[0];
-                match trump_card {
-                    Card::SpecialCard(special_card) => match special_card {
-                        SpecialCard::Wizard => {
-                            let dealing_player = (self.starting_player + self.players.len() - 1)
-                                % self.players.len();
-                            let game_state = self.game_state();
-                            self.trump_suit = Some(
-                                self.players
-                                    .get_mut(dealing_player)
-                                    .unwrap()
-                                    .choose_trump(&game_state),
-                            );
-                        }
-                        SpecialCard::Jester => {
-                            self.trump_suit = None;
-                        }
-                    },
-                    Card::NormalCard(normal_card) => {
-                        self.trump_suit = Some(normal_card.suit);
-                    }
-                }
+    /// Send players and spectators the events since the last update, and new spectators every
+    /// event so far
+    fn send_events(&mut self) {
+        let events = self.engine.events();
+        let state = self.engine.game_state();
+        let hands: Vec<Vec<Card>> = (0..self.engine.num_players())
+            .map(|i| self.engine.hand(i).to_vec())
+            .collect();
+
+        for player in self.players.iter_mut() {
+            for event in events[self.events_sent..].iter() {
+                player.event(event, &state);
             }
-            Err(_) => {
-                self.trump_suit = None;
+        }
+        for spectator in self.spectators.iter_mut() {
+            for event in events[self.events_sent..].iter() {
+                spectator.event(event, &state, &hands);
             }
-        };
-    }
-
-    /// Let each player bid
-    fn bid(&mut self) {
-        for i in 0..self.players.len() {
-            let players_turn = i + self.starting_player % self.players.len();
-            let state = self.game_state();
-            self.players[players_turn].bid(&state);
         }
-    }
-
-    /// Each player plays cards until no cards remain in their hands
-    fn play_cards(&mut self) {
-        // Which player is leading for this trick
-        let leading_player = self.starting_player;
-
-        // For each trick
-        for _ in 0..self.round {
-            // Keep track of what cards have been played for this trick
-            let mut played_cards = Vec::new();
-
-            // For each player
-            for i in 0..self.players.len() {
-                // Which player is currently playing a card
-                let playing_player = (leading_player + i) % self.players.len();
-
-                // Get game state - including which cards have been played so far and who started
-                let mut state = self.game_state();
-                if let Value::Object(ref mut map) = state {
-                    map.insert(
-                        "played_cards".to_string(),
-                        serde_json::to_value(&played_cards).unwrap(),
-                    );
-                    map.insert(
-                        "leading_player".to_string(),
-                        Value::Number(playing_player.into()),
-                    );
-                    let leading_suit = WizardGame::leading_suit(&played_cards);
-                    map.insert(
-                        "leading_suit".to_string(),
-                        serde_json::to_value(&leading_suit).unwrap(),
-                    );
+        if let Some(receiver) = &self.spectator_arrivals {
+            for mut spectator in receiver.try_iter() {
+                println!("{} is watching", spectator.name());
+                for event in events.iter() {
+                    spectator.event(event, &state, &hands);
                 }
-
-                // Player plays a card
-                let played_card = self.players[playing_player].play_card(&state);
-                played_cards.push(played_card);
+                self.spectators.push(spectator);
             }
-
-            // Update taken tricks
-            let winning_player = self.trick_winner(&played_cards, leading_player);
-            self.players[winning_player].won_trick();
         }
+        self.events_sent = events.len();
     }
 
-    /// Leading suit given a vector of cards.
-    ///
-    /// If a Wizard has been played, then leading suit is set to none since suit no longer matters.
-    fn leading_suit(cards: &Vec<Card>) -> Option<Suit> {
-        // If there are any wizards, then there is no leading suit
-        if cards.contains(&Card::SpecialCard(SpecialCard::Wizard)) {
-            return None;
+    /// Tell every player that player `i` ran out of time and what was decided for them
+    fn announce_timeout(&mut self, i: usize, decision: &impl Debug) {
+        let msg = format!(
+            "Player {} ran out of time. Decided for them: {:?}",
+            i, decision
+        );
+        eprintln!("{}", msg);
+        for player in self.players.iter_mut() {
+            player.turn_timed_out(i, &msg);
         }
+    }
 
-        // Return the suit of the first normal card
-        for card in cards.iter() {
-            if let Card::NormalCard(normal_card) = card {
-                return Some(normal_card.suit);
+    /// Swap in the connections of players who rejoined since the last check
+    fn resume_reconnected(&mut self) {
+        loop {
+            let reconnection = match &self.reconnections {
+                Some((receiver, _)) => receiver.try_recv(),
+                None => return,
+            };
+            match reconnection {
+                Ok(reconnection) => self.resume(reconnection),
+                Err(_) => return,
             }
         }
-
-        // If there are only jesters, there is no trump suit
-        None
     }
 
-    fn trick_winner(&self, cards: &Vec<Card>, leading_player: usize) -> usize {
-        let mut winning_player = 0;
-        let mut winning_card = &cards[0];
-
-        let leading_suit = WizardGame::leading_suit(cards);
+    /// Pause the game until the player in seat `i` rejoins
+    ///
+    /// # Errors
+    /// If reconnections are not accepted or the player does not rejoin in time
+    fn wait_for_reconnection(&mut self, i: usize) -> Result<()> {
+        let timeout = match &self.reconnections {
+            Some((_, timeout)) => *timeout,
+            None => bail!("Player {} disconnected", i),
+        };
+        eprintln!("Player {} disconnected. Waiting for them to rejoin.", i);
 
-        for (i, new_card) in cards.iter().enumerate().skip(1) {
-            if WizardGame::is_better_card(&winning_card, &new_card, leading_suit, self.trump_suit) {
-                winning_player = i;
-                winning_card = new_card;
+        let deadline = Instant::now() + timeout;
+        loop {
+            let reconnection = match &self.reconnections {
+                Some((receiver, _)) => {
+                    receiver.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                }
+                None => unreachable!("Reconnections were checked above"),
+            };
+            let Ok(reconnection) = reconnection else {
+                bail!("Player {} did not rejoin within {:?}", i, timeout);
+            };
+
+            let seat = reconnection.seat;
+            self.resume(reconnection);
+            if seat == i {
+                return Ok(());
             }
         }
+    }
 
-        (winning_player + leading_player) % self.players.len()
+    /// Seat a rejoined client and send it the current state of the game
+    fn resume(&mut self, reconnection: Reconnection) {
+        let seat = reconnection.seat;
+        println!("Player {} rejoined", seat);
+        let player = &mut self.players[seat];
+        player.reconnect(reconnection.connection);
+        player.resume(seat, self.engine.hand(seat), &self.engine.game_state());
     }
 
-    /// Checks to see if a card is better than another card.
+    /// Message telling a player why the engine rejected their decision
     ///
-    /// # Arguments
-    ///
-    /// * `base_card` - Card to compare to
-    /// * `is_better` - Card to compare
-    /// * `leading_suit` - Suit that has been lead
-    /// * `trump_suit` - Trump suit
-    ///
-    /// # Returns
-    ///
-    /// True if `is_better` is a better card than `base_card`
-    fn is_better_card(
-        base_card: &Card,
-        is_better: &Card,
-        leading_suit: Option<Suit>,
-        trump_suit: Option<Suit>,
-    ) -> bool {
-        // base_card = Wizard always is better
-        if let Card::SpecialCard(special_card) = base_card {
-            if let SpecialCard::Wizard = special_card {
-                return false;
-            }
-        }
-        // is_better = Jester always loses
-        // is_better = Wizard -> Wins if first card isn't a wizard
-        if let Card::SpecialCard(special_card) = is_better {
-            match special_card {
-                SpecialCard::Wizard => return true,
-                SpecialCard::Jester => return false,
-            }
-        }
-        // base_card = Jester -> Loses unless is_better is a jester
-        if let Card::SpecialCard(special_card) = base_card {
-            if let SpecialCard::Jester = special_card {
-                return true;
-            }
-        }
-
-        // --- All special cards already handled. Only normal left --- //
-        let base_card = match base_card {
-            Card::NormalCard(normal_card) => normal_card,
-            Card::SpecialCard(_) => {
-                panic!("Should have handled all special card cases before this.")
-            }
-        };
-        let is_better = match is_better {
-            Card::NormalCard(normal_card) => normal_card,
-            Card::SpecialCard(_) => {
-                panic!("Should have handled all special card cases before this.")
-            }
-        };
-
-        // Same suit, high card wins
-        if base_card.suit == is_better.suit {
-            return base_card.rank < is_better.rank;
-        }
-        // Different suit, trump suit wins first, then leading suit
-        if Some(base_card.suit) == trump_suit {
-            return false;
-        }
-        if Some(is_better.suit) == trump_suit {
-            return true;
-        }
-        if Some(base_card.suit) == leading_suit {
-            return false;
-        }
-        if Some(is_better.suit) == leading_suit {
-            return true;
+    /// # Errors
+    /// If the engine failed for a reason other than the player breaking the rules
+    fn rejection(error: Error) -> Result<ErrorMessage> {
+        match error.downcast_ref::<RuleViolation>() {
+            Some(violation) => Ok(ErrorMessage {
+                reason: violation.into(),
+                msg: violation.to_string(),
+            }),
+            // Not the player's fault. Something is wrong with the game itself
+            None => Err(error),
         }
-
-        // Default to first card winning
-        false
     }
 
-    /// Update each players' scores based on the results of the round
-    fn update_player_scores(&mut self) {
+    /// Ask every player who has not bid yet for a blind bid at the same time
+    ///
+    /// Rejected bids are answered with an `Action::Error` and asked for again, up to
+    /// `MAX_ATTEMPTS` times before bidding 0 for the player. Once every bid is in, all players
+    /// are told the bids.
+    fn collect_blind_bids(&mut self) -> Result<()> {
+        let limit = self.timeouts.limit(Turn::BlindBid);
+        let deadline = limit.map(|limit| Instant::now() + limit);
         for player in self.players.iter_mut() {
-            player.update_score();
+            player.set_deadline(deadline);
         }
-    }
 
-    /// Game state
-    fn game_state(&self) -> Value {
-        let mut state = Map::new();
+        let mut attempts = 0;
+        while attempts < MAX_ATTEMPTS {
+            let pending = self.engine.pending_bidders();
+            if pending.is_empty() {
+                break;
+            }
 
-        // Game state
-        state.insert(
-            "starting_player".to_string(),
-            Value::String(format!("player-{}", self.starting_player).to_string()),
-        );
-        state.insert("round".to_string(), Value::Number(self.round.into()));
-        state.insert(
-            "trump_suit".to_string(),
-            match &self.trump_suit {
-                Some(suit) => serde_json::to_value(&suit).unwrap(),
-                None => Value::Null,
-            },
-        );
-        state.insert(
-            "player_count".to_string(),
-            Value::Number(self.players.len().into()),
-        );
+            self.resume_reconnected();
+            let state = self.engine.game_state();
+            let engine = &self.engine;
+            let responses: Vec<(usize, Result<u8>)> = thread::scope(|scope| {
+                let requests: Vec<_> = self
+                    .players
+                    .iter_mut()
+                    .enumerate()
+                    .filter(|(i, _)| pending.contains(i))
+                    .map(|(i, player)| {
+                        let state = &state;
+                        let request =
+                            scope.spawn(move || player.bid(i, engine.hand(i), &[], None, state));
+                        (i, request)
+                    })
+                    .collect();
+
+                requests
+                    .into_iter()
+                    .map(|(i, request)| (i, request.join().expect("Bid request panicked")))
+                    .collect()
+            });
+
+            // Dropped players are asked again once they rejoin without using up an attempt
+            let mut rejected = false;
+            for (i, response) in responses {
+                let error = match response {
+                    Ok(bid) => match self.engine.bid(i, bid) {
+                        Ok(()) => continue,
+                        Err(e) => WizardGame::rejection(e)?,
+                    },
+                    Err(e) if e.is::<Disconnected>() => {
+                        self.wait_for_reconnection(i)?;
+                        self.players[i].set_deadline(limit.map(|limit| Instant::now() + limit));
+                        continue;
+                    }
+                    Err(e) if e.is::<TimedOut>() => {
+                        self.engine.bid(i, 0)?;
+                        self.announce_timeout(i, &0);
+                        continue;
+                    }
+                    Err(e) => ErrorMessage {
+                        reason: ErrorReason::UnexpectedAction,
+                        msg: e.to_string(),
+                    },
+                };
 
-        // Player states
-        for (i, player) in self.players.iter().enumerate() {
-            let mut player_state = Map::new();
+                rejected = true;
+                eprintln!("Player {} response rejected: {}", i, error.msg);
+                self.players[i].error(error);
+            }
+            if rejected {
+                attempts += 1;
+            }
+        }
 
-            player_state.insert("score".to_string(), Value::Number(player.score.into()));
-            player_state.insert(
-                "bid".to_string(),
-                match player.bid {
-                    Some(bid) => Value::Number(bid.into()),
-                    None => Value::Null,
-                },
-            );
-            player_state.insert(
-                "tricks_taken".to_string(),
-                Value::Number(player.tricks_taken.into()),
+        for i in self.engine.pending_bidders() {
+            eprintln!(
+                "Player {} failed to respond {} times. Deciding for them.",
+                i, MAX_ATTEMPTS
             );
+            self.engine.bid(i, 0)?;
+        }
 
-            state.insert(
-                format!("player-{}", i).to_string(),
-                Value::Object(player_state),
-            );
+        let bids = self.engine.bids();
+        let state = self.engine.game_state();
+        for player in self.players.iter_mut() {
+            player.bids_revealed(&bids, &state);
         }
 
-        Value::Object(state)
+        Ok(())
     }
 }
 
-/*
+/// Suit picked for a player who did not pick one: the suit they hold the most cards of
+///
+/// Ties go to the first suit, so the same hand always gets the same suit and a game with timeouts
+/// plays out the same from its seed.
+fn fallback_suit(hand: &[Card]) -> Suit {
+    Suit::iter()
+        .min_by_key(|suit| {
+            let held = hand
+                .iter()
+                .filter(|card| matches!(card, Card::NormalCard(card) if card.suit == *suit))
+                .count();
+            Reverse(held)
+        })
+        .expect("There are suits")
+}
+
+/// Card least likely to win a trick, played or passed for a player who did not pick one
+///
+/// Jesters come first, then suited cards by rank, then the remaining special cards.
+fn weakest_card(cards: &[Card]) -> Card {
+    *cards
+        .iter()
+        .min_by_key(|card| match card {
+            Card::SpecialCard(SpecialCard::Jester) => 0,
+            Card::NormalCard(card) => card.rank.value(),
+            Card::SpecialCard(_) => u8::MAX,
+        })
+        .expect("Player has a card to choose from")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cards::normal_card::NormalCard;
     use crate::cards::rank::Rank;
-    use strum::IntoEnumIterator;
-
-    #[test]
-    fn not_enough_players() {
-        assert!(WizardGame::new(2).is_err());
-        assert!(WizardGame::new(1).is_err());
-        assert!(WizardGame::new(0).is_err());
-    }
-
-    #[test]
-    fn too_many_players() {
-        assert!(WizardGame::new(7).is_err());
-        assert!(WizardGame::new(8).is_err());
-    }
-
-    #[test]
-    fn enough_players() {
-        for i in 3..=6 {
-            assert!(!WizardGame::new(i).is_err());
-        }
-    }
-
-    #[test]
-    fn player_creation() {
-        for num_players in 3..=6 {
-            let mut game = WizardGame::new(num_players).unwrap();
-
-            assert_eq!(num_players, game.players.len());
-
-            for player in game.players {
-                assert_eq!(0, player.score);
-                assert_eq!(0, player.cards.len());
-            }
-        }
-    }
-
-    #[test]
-    fn play_four_player_game() {
-        let mut game = WizardGame::new(4).unwrap();
-
-        game.play_game();
-
-        assert_eq!(15, game.round);
-    }
-
-    fn get_bid_from_state(state: &Value, player: usize) -> Option<u8> {
-        let player_str = format!("player-{}", player).to_string();
-
-        match state.get(player_str).unwrap().get("bid").unwrap() {
-            Value::Number(bid) => Some(bid.as_u64().unwrap() as u8),
-            Value::Null => None,
-            _ => panic!("Invalid bid value!"),
-        }
-    }
-
-    #[test]
-    fn game_state_inital() {
-        let game = WizardGame::new(4).unwrap();
-
-        let state = game.game_state();
-        println!("{}", state);
-
-        for i in 0..game.players.len() {
-            let bid = get_bid_from_state(&state, i);
-            assert_eq!(None, bid);
-        }
-    }
-
-    #[test]
-    fn game_state_bid() {
-        let mut game = WizardGame::new(4).unwrap();
-        game.bid();
-
-        let state = game.game_state();
-        println!("{}", state);
-
-        for i in 0..game.players.len() {
-            let bid = get_bid_from_state(&state, i);
-            assert_eq!(Some(0), bid);
-        }
-    }
 
     #[test]
-    fn trick_winner() {
-        let mut game = WizardGame::new(3).unwrap();
-
-        // Wizard
-        for i in 0..3 {
-            assert_eq!(
-                i,
-                game.trick_winner(
-                    &vec![
-                        Card::SpecialCard(SpecialCard::Wizard),
-                        Card::SpecialCard(SpecialCard::Wizard),
-                        Card::SpecialCard(SpecialCard::Wizard),
-                    ],
-                    i
-                )
-            );
-        }
-        assert_eq!(
-            0,
-            game.trick_winner(
-                &vec![
-                    Card::NormalCard(NormalCard {
-                        suit: Suit::Spade,
-                        rank: Rank::Ace
-                    }),
-                    Card::SpecialCard(SpecialCard::Wizard),
-                    Card::NormalCard(NormalCard {
-                        suit: Suit::Spade,
-                        rank: Rank::Queen
-                    }),
-                ],
-                2
-            )
-        );
-        // Jester
-        for i in 0..3 {
-            assert_eq!(
-                i,
-                game.trick_winner(
-                    &vec![
-                        Card::SpecialCard(SpecialCard::Jester),
-                        Card::SpecialCard(SpecialCard::Jester),
-                        Card::SpecialCard(SpecialCard::Jester),
-                    ],
-                    i
-                )
-            );
-        }
-        assert_eq!(
-            1,
-            game.trick_winner(
-                &vec![
-                    Card::SpecialCard(SpecialCard::Jester),
-                    Card::SpecialCard(SpecialCard::Jester),
-                    Card::NormalCard(NormalCard {
-                        suit: Suit::Spade,
-                        rank: Rank::Queen
-                    }),
-                ],
-                2
-            )
-        );
-        // Normal cards
+    fn fallback_suit_is_the_longest_suit_in_hand() {
+        let card = |suit, rank| Card::NormalCard(NormalCard { suit, rank });
+        let hand = [
+            card(Suit::Heart, Rank::Two),
+            card(Suit::Spade, Rank::Ace),
+            card(Suit::Heart, Rank::Nine),
+            Card::SpecialCard(SpecialCard::Wizard),
+        ];
+        assert_eq!(Suit::Heart, fallback_suit(&hand));
+
+        let first_suit = Suit::iter().next().unwrap();
+        assert_eq!(first_suit, fallback_suit(&[]));
         assert_eq!(
-            2,
-            game.trick_winner(
-                &vec![
-                    Card::NormalCard(NormalCard {
-                        suit: Suit::Spade,
-                        rank: Rank::Ace
-                    }),
-                    Card::NormalCard(NormalCard {
-                        suit: Suit::Spade,
-                        rank: Rank::King
-                    }),
-                    Card::NormalCard(NormalCard {
-                        suit: Suit::Spade,
-                        rank: Rank::Queen
-                    }),
-                ],
-                2
-            )
+            first_suit,
+            fallback_suit(&[Card::SpecialCard(SpecialCard::Jester)])
         );
-
-        game.trump_suit = Some(Suit::Heart);
-        assert_eq!(
-            0,
-            game.trick_winner(
-                &vec![
-                    Card::NormalCard(NormalCard {
-                        suit: Suit::Spade,
-                        rank: Rank::Two
-                    }),
-                    Card::NormalCard(NormalCard {
-                        suit: Suit::Diamond,
-                        rank: Rank::King
-                    }),
-                    Card::NormalCard(NormalCard {
-                        suit: Suit::Club,
-                        rank: Rank::Ace
-                    }),
-                ],
-                0
-            )
-        );
-        assert_eq!(
-            2,
-            game.trick_winner(
-                &vec![
-                    Card::NormalCard(NormalCard {
-                        suit: Suit::Spade,
-                        rank: Rank::Ace
-                    }),
-                    Card::NormalCard(NormalCard {
-                        suit: Suit::Heart,
-                        rank: Rank::King
-                    }),
-                    Card::NormalCard(NormalCard {
-                        suit: Suit::Spade,
-                        rank: Rank::Queen
-                    }),
-                ],
-                1
-            )
-        );
-    }
-
-    #[test]
-    fn is_better_card() {
-        // --- Wizards tests --- //
-        assert!(!WizardGame::is_better_card(
-            &Card::SpecialCard(SpecialCard::Wizard),
-            &Card::SpecialCard(SpecialCard::Wizard),
-            Some(Suit::Spade),
-            Some(Suit::Spade)
-        ));
-        assert!(!WizardGame::is_better_card(
-            &Card::SpecialCard(SpecialCard::Wizard),
-            &Card::SpecialCard(SpecialCard::Jester),
-            Some(Suit::Spade),
-            Some(Suit::Spade)
-        ));
-        assert!(WizardGame::is_better_card(
-            &Card::SpecialCard(SpecialCard::Jester),
-            &Card::SpecialCard(SpecialCard::Wizard),
-            Some(Suit::Spade),
-            Some(Suit::Spade)
-        ));
-
-        for trump_suit in Suit::iter() {
-            for lead_suit in Suit::iter() {
-                for suit in Suit::iter() {
-                    for rank in Rank::iter() {
-                        assert!(WizardGame::is_better_card(
-                            &Card::NormalCard(NormalCard { suit, rank }),
-                            &Card::SpecialCard(SpecialCard::Wizard),
-                            Some(trump_suit),
-                            Some(lead_suit)
-                        ));
-                        assert!(!WizardGame::is_better_card(
-                            &Card::SpecialCard(SpecialCard::Wizard),
-                            &Card::NormalCard(NormalCard { suit, rank }),
-                            Some(trump_suit),
-                            Some(lead_suit)
-                        ));
-                    }
-                }
-            }
-        }
-
-        // --- Jester tests --- //
-        assert!(!WizardGame::is_better_card(
-            &Card::SpecialCard(SpecialCard::Jester),
-            &Card::SpecialCard(SpecialCard::Jester),
-            Some(Suit::Spade),
-            Some(Suit::Spade)
-        ));
-
-        for trump_suit in Suit::iter() {
-            for lead_suit in Suit::iter() {
-                for suit in Suit::iter() {
-                    for rank in Rank::iter() {
-                        assert!(!WizardGame::is_better_card(
-                            &Card::NormalCard(NormalCard { suit, rank }),
-                            &Card::SpecialCard(SpecialCard::Jester),
-                            Some(trump_suit),
-                            Some(lead_suit)
-                        ));
-                        assert!(WizardGame::is_better_card(
-                            &Card::SpecialCard(SpecialCard::Jester),
-                            &Card::NormalCard(NormalCard { suit, rank }),
-                            Some(trump_suit),
-                            Some(lead_suit)
-                        ));
-                    }
-                }
-            }
-        }
-
-        // --- Normal card tests --- //
-        // Trump suit wins
-        assert!(WizardGame::is_better_card(
-            &Card::NormalCard(NormalCard {
-                suit: Suit::Heart,
-                rank: Rank::Ace
-            }),
-            &Card::NormalCard(NormalCard {
-                suit: Suit::Spade,
-                rank: Rank::Two
-            }),
-            Some(Suit::Heart),
-            Some(Suit::Spade)
-        ));
-        assert!(!WizardGame::is_better_card(
-            &Card::NormalCard(NormalCard {
-                suit: Suit::Heart,
-                rank: Rank::Ace
-            }),
-            &Card::NormalCard(NormalCard {
-                suit: Suit::Spade,
-                rank: Rank::Two
-            }),
-            Some(Suit::Spade),
-            Some(Suit::Heart),
-        ));
-        // Lead suit wins
-        assert!(WizardGame::is_better_card(
-            &Card::NormalCard(NormalCard {
-                suit: Suit::Heart,
-                rank: Rank::Ace
-            }),
-            &Card::NormalCard(NormalCard {
-                suit: Suit::Spade,
-                rank: Rank::Two
-            }),
-            Some(Suit::Spade),
-            Some(Suit::Diamond)
-        ));
-        assert!(!WizardGame::is_better_card(
-            &Card::NormalCard(NormalCard {
-                suit: Suit::Heart,
-                rank: Rank::Ace
-            }),
-            &Card::NormalCard(NormalCard {
-                suit: Suit::Spade,
-                rank: Rank::Two
-            }),
-            Some(Suit::Heart),
-            Some(Suit::Diamond)
-        ));
-        // High card of same suit wins
-        assert!(WizardGame::is_better_card(
-            &Card::NormalCard(NormalCard {
-                suit: Suit::Heart,
-                rank: Rank::Two
-            }),
-            &Card::NormalCard(NormalCard {
-                suit: Suit::Heart,
-                rank: Rank::Ace
-            }),
-            Some(Suit::Club),
-            Some(Suit::Diamond)
-        ));
-        assert!(!WizardGame::is_better_card(
-            &Card::NormalCard(NormalCard {
-                suit: Suit::Heart,
-                rank: Rank::Ace
-            }),
-            &Card::NormalCard(NormalCard {
-                suit: Suit::Heart,
-                rank: Rank::Two
-            }),
-            Some(Suit::Club),
-            Some(Suit::Diamond)
-        ));
-        // First card of none lead nor trump suit wins
-        assert!(!WizardGame::is_better_card(
-            &Card::NormalCard(NormalCard {
-                suit: Suit::Heart,
-                rank: Rank::Two
-            }),
-            &Card::NormalCard(NormalCard {
-                suit: Suit::Spade,
-                rank: Rank::Ace
-            }),
-            Some(Suit::Club),
-            Some(Suit::Diamond)
-        ));
-        assert!(!WizardGame::is_better_card(
-            &Card::NormalCard(NormalCard {
-                suit: Suit::Heart,
-                rank: Rank::Ace
-            }),
-            &Card::NormalCard(NormalCard {
-                suit: Suit::Spade,
-                rank: Rank::Two
-            }),
-            Some(Suit::Club),
-            Some(Suit::Diamond)
-        ));
     }
 }
-    */
@@ -4,3 +4,4 @@ pub mod game;
 pub mod network;
 pub mod players;
 pub mod server;
+pub mod simulation;
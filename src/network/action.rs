@@ -1,32 +1,342 @@
+use crate::cards::card::Card;
+use crate::cards::suit::Suit;
+use crate::game::event::GameEvent;
+use crate::game::rule_violation::RuleViolation;
+use crate::game::rules::GameRules;
+use crate::game::state::{GameState, PlacedBid};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// Messages sent from the server to a client
+///
+/// Serialized with an "action" field naming the variant alongside the payload's fields
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action")]
 pub enum Action {
-    //
-    Bid,
-    // "trump" and "hand" will be supplied
-    ChooseTrump,
-    // "msg" will be supplied detailing what we are confirming
-    Confirmation,
-    // "host" and "port" will be supplied
-    Connect,
-    // TODO - remove
-    Card,
+    // Client should respond with `ClientAction::AdjustBid`
+    AdjustBid(AdjustBidRequest),
+    // Client should respond with `ClientAction::Bid`
+    Bid(BidRequest),
+    // Every blind bid is in. No response expected
+    BidsRevealed(BidsRevealedMessage),
+    // Client should respond with `ClientAction::ChooseTrump`
+    ChooseTrump(ChooseTrumpRequest),
+    // Confirm the client has connected
+    Confirmation(ConfirmationMessage),
     // Final Game Stats
-    EndGame,
-    // "hand", "playable_cards", "played_card" will be supplied. "played_card" should be the
-    // client's response
-    PlayCard,
+    EndGame(EndGameMessage),
+    // Client's last response was rejected. The request will be sent again
+    Error(ErrorMessage),
+    // Something happened in the game being watched. No response expected
+    Event(EventMessage),
+    // Client should respond with `ClientAction::PassCard`
+    PassCard(PassCardRequest),
+    // Client should respond with `ClientAction::PlayCard` containing one of "playable_cards"
+    PlayCard(PlayCardRequest),
+    // Client rejoined its seat. The pending request, if any, is sent again next
+    Resume(ResumeMessage),
     // Tell client that server is starting the game
-    StartGame,
+    StartGame(StartGameMessage),
+    // Client sat down at a lobby table. The game starts once every seat is taken
+    TableJoined(TableJoinedMessage),
+    // Client got up from its lobby table
+    TableLeft(TableLeftMessage),
+    // Lobby tables, sent in answer to `ClientAction::ListTables`
+    Tables(TablesMessage),
+    // A player ran out of time and the server decided for them. No response expected
+    TurnTimedOut(TurnTimedOutMessage),
+}
+
+/// Messages sent from a client to the server
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action")]
+pub enum ClientAction {
+    // Raise (1) or lower (-1) the bid
+    AdjustBid {
+        adjustment: i8,
+    },
+    Bid {
+        bid: u8,
+    },
+    ChooseTrump {
+        trump: Suit,
+    },
+    // Open a lobby table and sit down at it
+    CreateTable {
+        num_players: usize,
+        #[serde(default)]
+        rules: GameRules,
+    },
+    // First message sent after connecting to the server. A client rejoining a game in progress
+    // sends the session it was given in its `ConfirmationMessage`
+    Join {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        session: Option<String>,
+    },
+    // Sit down at an open lobby table
+    JoinTable {
+        table: u64,
+    },
+    // Get up from the lobby table before its game starts
+    LeaveTable,
+    // Ask the lobby for its tables
+    ListTables,
+    PassCard {
+        card: Card,
+    },
+    // Watch a game without a seat, instead of joining it. A lobby needs the table to watch.
+    // Spectators who give the server's admin token see every hand.
+    Spectate {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        table: Option<u64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        admin_token: Option<String>,
+    },
+    // A played Juggler or Cloud names its chosen suit, e.g. "CloudH"
+    PlayCard {
+        played_card: Card,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BidRequest {
+    pub hand: Vec<Card>,
+    /// Bids already made this round, in bidding order
+    pub bids: Vec<PlacedBid>,
+    /// Seat that bids next, which is the player receiving this request
+    pub next_bidder: usize,
+    /// Bid this player is not allowed to make under restricted bidding
+    pub forbidden_bid: Option<u8>,
+    pub state: GameState,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BidsRevealedMessage {
+    /// Every bid this round, in bidding order
+    pub bids: Vec<PlacedBid>,
+    pub state: GameState,
+}
+
+/// Player won a trick with a Cloud in it and must raise or lower their bid by one
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdjustBidRequest {
+    pub hand: Vec<Card>,
+    /// Bid before the adjustment
+    pub bid: u8,
+    pub state: GameState,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChooseTrumpRequest {
+    pub hand: Vec<Card>,
+    pub state: GameState,
+}
+
+/// A Juggler was played, so every player passes a card from `hand` to the player on their left
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PassCardRequest {
+    pub hand: Vec<Card>,
+    pub state: GameState,
 }
 
-impl Action {
-    pub fn serde_find_action(data: &Vec<u8>) -> Result<(Action, Value), serde_json::Error> {
-        let deserialized: serde_json::Value = serde_json::from_slice(data)?;
-        let action = serde_json::from_value(deserialized["action"].clone())?;
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayCardRequest {
+    pub hand: Vec<Card>,
+    pub playable_cards: Vec<Card>,
+    pub state: GameState,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfirmationMessage {
+    pub msg: String,
+    /// Token to rejoin the same seat with if the connection drops. Spectators have none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+}
+
+/// Everything a rejoining client needs to pick the game back up
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResumeMessage {
+    pub seat: usize,
+    pub hand: Vec<Card>,
+    pub state: GameState,
+}
+
+/// Machine readable reason a client's response was rejected
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ErrorReason {
+    /// Response could not be parsed or was not the action that was requested
+    UnexpectedAction,
+    NotYourTurn,
+    InvalidBid,
+    ForbiddenBid,
+    InvalidBidAdjustment,
+    SuitNotChosen,
+    CardNotInHand,
+    CardNotPlayable,
+    /// Lobby table can not be played with the requested seat count and rules
+    InvalidTable,
+    /// Lobby table does not exist or has no free seat
+    TableUnavailable,
+    /// Admin token given to watch as an omniscient spectator is wrong
+    NotAuthorized,
+}
+
+impl From<&RuleViolation> for ErrorReason {
+    fn from(violation: &RuleViolation) -> Self {
+        match violation {
+            RuleViolation::NotYourTurn { .. } => ErrorReason::NotYourTurn,
+            RuleViolation::InvalidBid { .. } => ErrorReason::InvalidBid,
+            RuleViolation::ForbiddenBid { .. } => ErrorReason::ForbiddenBid,
+            RuleViolation::InvalidBidAdjustment { .. } => ErrorReason::InvalidBidAdjustment,
+            RuleViolation::SuitNotChosen { .. } => ErrorReason::SuitNotChosen,
+            RuleViolation::CardNotInHand { .. } => ErrorReason::CardNotInHand,
+            RuleViolation::CardNotPlayable { .. } => ErrorReason::CardNotPlayable,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorMessage {
+    pub reason: ErrorReason,
+    /// Human readable description of the error
+    pub msg: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TurnTimedOutMessage {
+    /// Seat that ran out of time
+    pub player: usize,
+    /// Human readable description of the decision made for them
+    pub msg: String,
+}
+
+/// Table in the lobby
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableInfo {
+    pub id: u64,
+    pub num_players: usize,
+    /// Names of the players sitting at the table, in seat order
+    pub players: Vec<String>,
+    pub rules: GameRules,
+    /// Whether the table is full and its game is being played
+    pub started: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TablesMessage {
+    pub tables: Vec<TableInfo>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableJoinedMessage {
+    pub table: TableInfo,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableLeftMessage {
+    pub table: u64,
+}
+
+/// Event sent to spectators as it happens
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventMessage {
+    /// Redacted unless the spectator is omniscient
+    pub event: GameEvent,
+    /// Game as it is now. When several events are sent at once this is after the last of them.
+    pub state: GameState,
+    /// Every player's hand, only sent to omniscient spectators
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hands: Option<Vec<Vec<Card>>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StartGameMessage {
+    pub state: GameState,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EndGameMessage {
+    pub state: GameState,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::normal_card::NormalCard;
+    use crate::cards::rank::Rank;
+    use serde_json::json;
+
+    #[test]
+    fn client_action_wire_format() {
+        let played_card = Card::NormalCard(NormalCard {
+            suit: Suit::Heart,
+            rank: Rank::Two,
+        });
+
+        assert_eq!(
+            json!({"action": "PlayCard", "played_card": "2H"}),
+            serde_json::to_value(ClientAction::PlayCard { played_card }).unwrap()
+        );
+        assert_eq!(
+            ClientAction::Bid { bid: 3 },
+            serde_json::from_value(json!({"action": "Bid", "bid": 3})).unwrap()
+        );
+    }
+
+    #[test]
+    fn join_session_is_optional() {
+        let join = ClientAction::Join {
+            name: "alice".to_string(),
+            session: None,
+        };
+        assert_eq!(
+            json!({"action": "Join", "name": "alice"}),
+            serde_json::to_value(&join).unwrap()
+        );
+        assert_eq!(
+            ClientAction::Join {
+                name: "alice".to_string(),
+                session: Some("abc".to_string()),
+            },
+            serde_json::from_value(json!({"action": "Join", "name": "alice", "session": "abc"}))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn create_table_rules_are_optional() {
+        assert_eq!(
+            ClientAction::CreateTable {
+                num_players: 4,
+                rules: GameRules::default(),
+            },
+            serde_json::from_value(json!({"action": "CreateTable", "num_players": 4})).unwrap()
+        );
+    }
+
+    #[test]
+    fn action_to_and_from_json() {
+        let action = Action::StartGame(StartGameMessage {
+            state: GameState {
+                round: 1,
+                num_rounds: 20,
+                hand_size: 1,
+                seed: None,
+                dealer: 0,
+                trump_suit: Some(Suit::Spade),
+                player_count: 3,
+                players: vec![Default::default(); 3],
+                played_cards: Vec::new(),
+                current_leader: 1,
+                leading_suit: None,
+            },
+        });
 
-        Ok((action, deserialized))
+        let json = serde_json::to_value(&action).unwrap();
+        assert_eq!("StartGame", json["action"]);
+        assert_eq!(1, json["state"]["round"]);
+        assert_eq!(action, serde_json::from_value(json).unwrap());
     }
 }
@@ -0,0 +1,122 @@
+use crate::network::frame::FrameReader;
+use crate::network::network::{network_listener, serialize_and_write_to_network, TimedOut};
+use anyhow::{bail, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io::Result as IoResult;
+use std::net::{SocketAddr, TcpStream};
+use std::time::Instant;
+
+/// Both directions of a single TCP connection
+///
+/// The stream is cloned so messages can be written while the reader keeps any partially
+/// received frames buffered.
+pub struct Connection {
+    reader: FrameReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    pub fn new(stream: TcpStream) -> IoResult<Connection> {
+        // Every message is written whole, so waiting to batch small writes only adds latency
+        stream.set_nodelay(true)?;
+        let writer = stream.try_clone()?;
+
+        Ok(Connection {
+            reader: FrameReader::new(stream),
+            writer,
+        })
+    }
+
+    /// Connect to a server
+    pub fn connect(host: &str, port: &str) -> IoResult<Connection> {
+        Connection::new(TcpStream::connect(format!("{}:{}", host, port))?)
+    }
+
+    /// Address of the other end of the connection
+    pub fn peer_addr(&self) -> IoResult<SocketAddr> {
+        self.writer.peer_addr()
+    }
+
+    /// Serialize a message and send it
+    pub fn send<T: Serialize>(&mut self, message: &T) {
+        serialize_and_write_to_network(&mut self.writer, message);
+    }
+
+    /// Wait for the next message
+    ///
+    /// This is a blocking operation
+    pub fn receive<T: DeserializeOwned>(&mut self) -> Result<T> {
+        network_listener(&mut self.reader)
+    }
+
+    /// Wait for the next message until `deadline`, or forever without one
+    ///
+    /// # Errors
+    /// `TimedOut` if no full message arrived before the deadline. A partly received message is
+    /// kept and finished by the next call.
+    pub fn receive_before<T: DeserializeOwned>(&mut self, deadline: Option<Instant>) -> Result<T> {
+        let Some(deadline) = deadline else {
+            return self.receive();
+        };
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            bail!(TimedOut);
+        }
+
+        self.writer.set_read_timeout(Some(remaining))?;
+        let message = self.receive();
+        self.writer.set_read_timeout(None)?;
+        message
+    }
+
+    /// Throw away messages that have already arrived without waiting for more
+    ///
+    /// A message that has only partly arrived is thrown away too once the rest of it arrives.
+    /// Returns how many were thrown away, counting a partial one. Used to drop answers to requests
+    /// that timed out.
+    pub fn discard_received(&mut self) -> usize {
+        if self.writer.set_nonblocking(true).is_err() {
+            return 0;
+        }
+        let mut discarded = 0;
+        while self.reader.read_frame().is_ok() {
+            discarded += 1;
+        }
+        if self.reader.discard_partial() {
+            discarded += 1;
+        }
+        if let Err(e) = self.writer.set_nonblocking(false) {
+            eprintln!("Failed to make connection blocking again: {}", e);
+        }
+        discarded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::frame::write_frame;
+    use std::io::Write;
+    use std::net::TcpListener;
+    use std::time::Duration;
+
+    #[test]
+    fn partly_received_message_is_discarded() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let mut connection = Connection::new(listener.accept().unwrap().0).unwrap();
+
+        let mut late = Vec::new();
+        write_frame(&mut late, b"\"late\"").unwrap();
+        client.write_all(&late[..5]).unwrap();
+        let deadline = Instant::now() + Duration::from_millis(200);
+        let timed_out = connection.receive_before::<String>(Some(deadline));
+        assert!(timed_out.unwrap_err().is::<TimedOut>());
+
+        assert_eq!(1, connection.discard_received());
+        client.write_all(&late[5..]).unwrap();
+        write_frame(&mut client, b"\"answer\"").unwrap();
+        assert_eq!("answer", connection.receive::<String>().unwrap());
+    }
+}
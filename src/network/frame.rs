@@ -0,0 +1,256 @@
+use std::io::{BufReader, Error, ErrorKind, Read, Result, Write};
+
+/// Largest message, in bytes, that will be sent or accepted
+pub const MAX_FRAME_SIZE: usize = 1024 * 1024;
+
+/// Number of bytes in the length prefix of each frame
+const HEADER_SIZE: usize = 4;
+
+/// Write a single frame: the payload length as a big-endian u32 followed by the payload
+pub fn write_frame<W: Write>(writer: &mut W, payload: &[u8]) -> Result<()> {
+    if payload.len() > MAX_FRAME_SIZE {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "Frame too large. Size: {}; Maximum: {}",
+                payload.len(),
+                MAX_FRAME_SIZE
+            ),
+        ));
+    }
+
+    let mut frame = Vec::with_capacity(HEADER_SIZE + payload.len());
+    frame.extend((payload.len() as u32).to_be_bytes());
+    frame.extend(payload);
+    writer.write_all(&frame)?;
+    writer.flush()
+}
+
+/// Reads length-prefixed frames written by `write_frame`
+///
+/// Reads are buffered, so any bytes received past the end of one frame are kept for the next
+/// call to `read_frame`. This lets several queued messages be read one after another. Bytes of a
+/// frame that was only partly received when a read failed, e.g. because it timed out, are kept
+/// too, so reading can pick up where it left off.
+pub struct FrameReader<R> {
+    reader: BufReader<R>,
+    /// Received bytes of the frame currently being read, including its header
+    partial: Vec<u8>,
+    /// Whether the frame currently being read is thrown away once it has been received
+    discard_partial: bool,
+}
+
+impl<R: Read> FrameReader<R> {
+    pub fn new(reader: R) -> FrameReader<R> {
+        FrameReader {
+            reader: BufReader::new(reader),
+            partial: Vec::new(),
+            discard_partial: false,
+        }
+    }
+
+    /// Underlying reader
+    pub fn get_ref(&self) -> &R {
+        self.reader.get_ref()
+    }
+
+    /// Read the next frame's payload
+    ///
+    /// This is a blocking operation.
+    ///
+    /// # Errors
+    /// * `ErrorKind::UnexpectedEof` if the connection closed
+    /// * `ErrorKind::InvalidData` if the frame is larger than `MAX_FRAME_SIZE`. The stream can
+    ///   not be recovered after this since the frame boundaries are lost.
+    /// * Any error from the underlying reader, e.g. `ErrorKind::WouldBlock` when a read timed out
+    pub fn read_frame(&mut self) -> Result<Vec<u8>> {
+        loop {
+            let payload = self.read_next()?;
+            if !std::mem::take(&mut self.discard_partial) {
+                return Ok(payload);
+            }
+        }
+    }
+
+    /// Throw away the partly received frame, if there is one
+    ///
+    /// The rest of the frame is still read from the stream to keep the frame boundaries, but is
+    /// skipped rather than returned by `read_frame`. Returns whether there was a partial frame.
+    pub fn discard_partial(&mut self) -> bool {
+        self.discard_partial = !self.partial.is_empty();
+        self.discard_partial
+    }
+
+    fn read_next(&mut self) -> Result<Vec<u8>> {
+        self.fill_to(HEADER_SIZE)?;
+
+        let header: [u8; HEADER_SIZE] = self.partial[..HEADER_SIZE].try_into().unwrap();
+        let size = u32::from_be_bytes(header) as usize;
+        if size > MAX_FRAME_SIZE {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Frame too large. Size: {}; Maximum: {}",
+                    size, MAX_FRAME_SIZE
+                ),
+            ));
+        }
+
+        self.fill_to(HEADER_SIZE + size)?;
+        let payload = self.partial.split_off(HEADER_SIZE);
+        self.partial.clear();
+
+        Ok(payload)
+    }
+
+    /// Read until `len` bytes of the current frame have been received
+    fn fill_to(&mut self, len: usize) -> Result<()> {
+        while self.partial.len() < len {
+            let received = self.partial.len();
+            self.partial.resize(len, 0);
+            let read = self.reader.read(&mut self.partial[received..]);
+            let n = *read.as_ref().unwrap_or(&0);
+            self.partial.truncate(received + n);
+
+            match read {
+                Ok(0) => return Err(Error::from(ErrorKind::UnexpectedEof)),
+                Ok(_) => {}
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// Reader that only hands out a few bytes at a time, like a slow socket
+    struct Trickle {
+        data: Cursor<Vec<u8>>,
+    }
+
+    impl Read for Trickle {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let n = buf.len().min(3);
+            self.data.read(&mut buf[..n])
+        }
+    }
+
+    #[test]
+    fn multiple_frames_in_one_read() {
+        let mut data = Vec::new();
+        write_frame(&mut data, b"first").unwrap();
+        write_frame(&mut data, b"").unwrap();
+        write_frame(&mut data, b"second").unwrap();
+
+        let mut reader = FrameReader::new(Cursor::new(data));
+        assert_eq!(b"first".to_vec(), reader.read_frame().unwrap());
+        assert_eq!(Vec::<u8>::new(), reader.read_frame().unwrap());
+        assert_eq!(b"second".to_vec(), reader.read_frame().unwrap());
+        assert_eq!(
+            ErrorKind::UnexpectedEof,
+            reader.read_frame().unwrap_err().kind()
+        );
+    }
+
+    #[test]
+    fn frame_split_across_reads() {
+        let mut data = Vec::new();
+        let payload = vec![7; 5000];
+        write_frame(&mut data, &payload).unwrap();
+        write_frame(&mut data, b"next").unwrap();
+
+        let mut reader = FrameReader::new(Trickle {
+            data: Cursor::new(data),
+        });
+        assert_eq!(payload, reader.read_frame().unwrap());
+        assert_eq!(b"next".to_vec(), reader.read_frame().unwrap());
+    }
+
+    /// Reader that fails every other read, like a socket whose read timed out
+    struct Stalling {
+        data: Trickle,
+        stalled: bool,
+    }
+
+    impl Read for Stalling {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            self.stalled = !self.stalled;
+            if self.stalled {
+                return Err(Error::from(ErrorKind::WouldBlock));
+            }
+            self.data.read(buf)
+        }
+    }
+
+    #[test]
+    fn partial_frame_survives_failed_reads() {
+        let mut data = Vec::new();
+        write_frame(&mut data, b"hello world").unwrap();
+
+        let mut reader = FrameReader::new(Stalling {
+            data: Trickle {
+                data: Cursor::new(data),
+            },
+            stalled: false,
+        });
+        let frame = loop {
+            match reader.read_frame() {
+                Ok(frame) => break frame,
+                Err(e) => assert_eq!(ErrorKind::WouldBlock, e.kind()),
+            }
+        };
+        assert_eq!(b"hello world".to_vec(), frame);
+    }
+
+    #[test]
+    fn discarded_partial_frame_is_skipped() {
+        let mut data = Vec::new();
+        write_frame(&mut data, b"late answer").unwrap();
+        write_frame(&mut data, b"answer").unwrap();
+
+        let mut reader = FrameReader::new(Stalling {
+            data: Trickle {
+                data: Cursor::new(data),
+            },
+            stalled: true,
+        });
+        assert_eq!(
+            ErrorKind::WouldBlock,
+            reader.read_frame().unwrap_err().kind()
+        );
+        assert!(reader.discard_partial());
+        let frame = loop {
+            match reader.read_frame() {
+                Ok(frame) => break frame,
+                Err(e) => assert_eq!(ErrorKind::WouldBlock, e.kind()),
+            }
+        };
+        assert_eq!(b"answer".to_vec(), frame);
+        assert!(!reader.discard_partial());
+    }
+
+    #[test]
+    fn oversized_frames_are_rejected() {
+        let mut data = Vec::new();
+        assert_eq!(
+            ErrorKind::InvalidInput,
+            write_frame(&mut data, &vec![0; MAX_FRAME_SIZE + 1])
+                .unwrap_err()
+                .kind()
+        );
+        assert!(data.is_empty());
+
+        data.extend((MAX_FRAME_SIZE as u32 + 1).to_be_bytes());
+        let mut reader = FrameReader::new(Cursor::new(data));
+        assert_eq!(
+            ErrorKind::InvalidData,
+            reader.read_frame().unwrap_err().kind()
+        );
+    }
+}
@@ -1,2 +1,5 @@
 pub mod action;
+pub mod connection;
+pub mod frame;
+#[allow(clippy::module_inception)]
 pub mod network;
@@ -1,9 +1,37 @@
-use crate::network::action::Action;
+use crate::network::frame::{write_frame, FrameReader};
 use anyhow::{bail, Result};
-use serde_json::Value;
-use std::io::{Read, Result as IoResult, Write};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt;
+use std::io::{ErrorKind, Result as IoResult};
 use std::net::{TcpListener, TcpStream};
 
+/// Other end of the connection went away
+///
+/// Returned by `network_listener` so callers can tell a lost connection apart from a bad message.
+#[derive(Debug)]
+pub struct Disconnected;
+
+impl fmt::Display for Disconnected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Client disconnected")
+    }
+}
+
+impl std::error::Error for Disconnected {}
+
+/// Nothing was received before the deadline
+#[derive(Debug)]
+pub struct TimedOut;
+
+impl fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Client took too long to respond")
+    }
+}
+
+impl std::error::Error for TimedOut {}
+
 /// Wait for an incoming connection
 ///
 /// This is a blocking operation
@@ -17,49 +45,39 @@ pub fn wait_for_incoming_connection(listener: &TcpListener) -> IoResult<TcpStrea
     }
 }
 
-/// Spawn a thread that will listen to incoming network connections over the stream
-/// Listen for data on a stream
+/// Listen for a message on a stream
 ///
-/// This is a blocking operation. Program will wait until a serde_json-able piece of data is sent
-/// over the stream
-pub fn network_listener(stream: &mut TcpStream) -> Result<(Action, Value)> {
-    let mut buffer = [0; 1024];
-    let mut data: Vec<u8> = Vec::new();
-
-    loop {
-        // Read n bytes
-        match stream.read(&mut buffer) {
-            Ok(0) => {
-                bail!("Client disconnected");
-            }
-            Ok(bytes_read) => {
-                data.extend(&buffer[..bytes_read]);
-                if let Ok((action, json)) = Action::serde_find_action(&data) {
-                    return Ok((action, json));
-                }
-            }
-            Err(e) => {
-                eprintln!("Failed to read from client: {}", e);
-                continue;
-            }
-        };
+/// This is a blocking operation. Program will wait until a full frame is received over the
+/// stream, then deserialize it into `T`
+pub fn network_listener<T: DeserializeOwned>(reader: &mut FrameReader<TcpStream>) -> Result<T> {
+    match reader.read_frame() {
+        Ok(frame) => Ok(serde_json::from_slice(&frame)?),
+        Err(e)
+            if matches!(
+                e.kind(),
+                ErrorKind::UnexpectedEof
+                    | ErrorKind::ConnectionReset
+                    | ErrorKind::ConnectionAborted
+            ) =>
+        {
+            bail!(Disconnected)
+        }
+        Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+            bail!(TimedOut)
+        }
+        Err(e) => bail!("Failed to read from client: {}", e),
     }
 }
 
-/// Write data to a stream
+/// Write a message to a stream
 pub fn network_writer(stream: &mut TcpStream, data: Vec<u8>) {
-    let chunk_size = 1024;
-
-    for chunk in data.as_slice().chunks(chunk_size) {
-        if let Err(e) = stream.write_all(chunk) {
-            eprintln!("Error sending chunk: {}", e);
-            break;
-        }
+    if let Err(e) = write_frame(stream, &data) {
+        eprintln!("Error sending message: {}", e);
     }
 }
 
 /// Serialize data then send over stream
-pub fn serialize_and_write_to_network(stream: &mut TcpStream, value: &Value) {
-    let serialized = serde_json::to_vec(&value).unwrap();
+pub fn serialize_and_write_to_network<T: Serialize>(stream: &mut TcpStream, value: &T) {
+    let serialized = serde_json::to_vec(value).unwrap();
     network_writer(stream, serialized);
 }
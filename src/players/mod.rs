@@ -1 +1,2 @@
 pub mod player;
+pub mod spectator;
@@ -1,203 +1,279 @@
 use crate::cards::card::Card;
 use crate::cards::suit::Suit;
-use crate::network::action::Action;
-use crate::network::network::{network_listener, network_writer};
-use serde_json::{from_value, json, Value};
-use std::net::TcpStream;
+use crate::game::event::GameEvent;
+use crate::game::state::{GameState, PlacedBid};
+use crate::network::action::{
+    Action, AdjustBidRequest, BidRequest, BidsRevealedMessage, ChooseTrumpRequest, ClientAction,
+    EndGameMessage, ErrorMessage, EventMessage, PassCardRequest, PlayCardRequest, ResumeMessage,
+    StartGameMessage, TableInfo, TableJoinedMessage, TableLeftMessage, TablesMessage,
+    TurnTimedOutMessage,
+};
+use crate::network::connection::Connection;
+use anyhow::{bail, Result};
+use std::time::Instant;
 
+/// Connection to a client playing in a seat of the game
+///
+/// The game rules live in `GameEngine`. A `Player` only forwards requests to its client and
+/// returns the client's answers.
 pub struct Player {
-    pub score: i16,
-    pub bid: Option<u8>,
-    pub cards: Vec<Card>,
-    pub tricks_taken: u8,
-    client_listener: TcpStream,
-    client_writer: TcpStream,
+    name: String,
+    /// Token the client rejoins this seat with after losing its connection
+    session: String,
+    connection: Connection,
+    /// Time the client must answer the next request by
+    deadline: Option<Instant>,
 }
 
 impl Player {
-    pub fn new(client_listener: TcpStream, client_writer: TcpStream) -> Player {
+    pub fn new(name: String, session: String, connection: Connection) -> Player {
         Player {
-            score: 0,
-            bid: None,
-            cards: Vec::new(),
-            tricks_taken: 0,
-            client_listener,
-            client_writer,
+            name,
+            session,
+            connection,
+            deadline: None,
         }
     }
 
-    fn network_writer(&mut self, value: &Value) {
-        let serialized = serde_json::to_vec(value).unwrap();
-        network_writer(&mut self.client_writer, serialized);
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn session(&self) -> &str {
+        &self.session
+    }
+
+    /// Replace a dropped connection with the one the client rejoined on
+    pub fn reconnect(&mut self, connection: Connection) {
+        self.connection = connection;
+    }
+
+    /// Limit how long requests wait for the client to answer. `None` waits forever.
+    pub fn set_deadline(&mut self, deadline: Option<Instant>) {
+        self.deadline = deadline;
+    }
+
+    fn network_writer(&mut self, action: &Action) {
+        self.connection.send(action);
+    }
+
+    /// Send a request the client must answer
+    ///
+    /// Anything the client sent since its last answer was read is a late answer to a request
+    /// that timed out, so it is thrown away rather than taken as the answer to this request.
+    fn request(&mut self, action: &Action) {
+        let discarded = self.connection.discard_received();
+        if discarded > 0 {
+            eprintln!("Ignored {} late responses from {}", discarded, self.name);
+        }
+        self.network_writer(action);
+    }
+
+    /// Wait for the client's answer until the deadline
+    fn response(&mut self) -> Result<ClientAction> {
+        self.connection.receive_before(self.deadline)
     }
 
     /// Inform clients that we are starting the game
-    pub fn start_game(&mut self, game_state: &Value) {
-        let send_start_game_action_json = json!({
-            "action": Action::StartGame,
-            "state": game_state,
-        });
-        self.network_writer(&send_start_game_action_json);
+    pub fn start_game(&mut self, game_state: &GameState) {
+        self.network_writer(&Action::StartGame(StartGameMessage {
+            state: game_state.clone(),
+        }));
+    }
+
+    pub fn end_game(&mut self, game_state: &GameState) {
+        self.network_writer(&Action::EndGame(EndGameMessage {
+            state: game_state.clone(),
+        }));
+    }
+
+    /// Tell the client every bid once blind bidding is over
+    pub fn bids_revealed(&mut self, bids: &[PlacedBid], game_state: &GameState) {
+        self.network_writer(&Action::BidsRevealed(BidsRevealedMessage {
+            bids: bids.to_vec(),
+            state: game_state.clone(),
+        }));
+    }
+
+    /// Bring a rejoined client up to date with its seat, hand and the game state
+    pub fn resume(&mut self, seat: usize, hand: &[Card], game_state: &GameState) {
+        self.network_writer(&Action::Resume(ResumeMessage {
+            seat,
+            hand: hand.to_vec(),
+            state: game_state.clone(),
+        }));
     }
 
-    pub fn end_game(&mut self, game_state: &Value) {
-        let send_end_game_action_json = json!({
-            "action": Action::EndGame,
-            "state": game_state,
-        });
-        self.network_writer(&send_end_game_action_json);
+    /// Wait until `deadline`, or forever without one, for the client's next lobby request
+    ///
+    /// # Errors
+    /// If the client disconnects or sends nothing before the deadline
+    pub fn lobby_action(&mut self, deadline: Option<Instant>) -> Result<ClientAction> {
+        self.connection.receive_before(deadline)
     }
 
-    pub fn bid(&mut self, game_state: &Value) {
+    /// Send the client the lobby's tables
+    pub fn tables(&mut self, tables: &[TableInfo]) {
+        self.network_writer(&Action::Tables(TablesMessage {
+            tables: tables.to_vec(),
+        }));
+    }
+
+    /// Tell the client it sat down at `table`
+    pub fn table_joined(&mut self, table: &TableInfo) {
+        self.network_writer(&Action::TableJoined(TableJoinedMessage {
+            table: table.clone(),
+        }));
+    }
+
+    /// Tell the client it got up from `table`
+    pub fn table_left(&mut self, table: u64) {
+        self.network_writer(&Action::TableLeft(TableLeftMessage { table }));
+    }
+
+    /// Tell the client that `player` ran out of time and what was decided for them
+    pub fn turn_timed_out(&mut self, player: usize, msg: &str) {
+        self.network_writer(&Action::TurnTimedOut(TurnTimedOutMessage {
+            player,
+            msg: msg.to_string(),
+        }));
+    }
+
+    /// Tell the client about an event, redacted so it shows nobody's hand
+    pub fn event(&mut self, event: &GameEvent, game_state: &GameState) {
+        self.network_writer(&Action::Event(EventMessage {
+            event: event.redacted(),
+            state: game_state.clone(),
+            hands: None,
+        }));
+    }
+
+    /// Tell the client its last response was rejected
+    pub fn error(&mut self, error: ErrorMessage) {
+        self.network_writer(&Action::Error(error));
+    }
+
+    /// Ask the client for a bid
+    ///
+    /// # Arguments
+    ///
+    /// * `seat` - Seat this player sits in
+    /// * `bids` - Bids already made this round, in bidding order
+    /// * `forbidden_bid` - Bid the player may not make under restricted bidding
+    ///
+    /// # Errors
+    /// If the client disconnects, misses the deadline or responds with anything other than a bid
+    pub fn bid(
+        &mut self,
+        seat: usize,
+        hand: &[Card],
+        bids: &[PlacedBid],
+        forbidden_bid: Option<u8>,
+        game_state: &GameState,
+    ) -> Result<u8> {
         // Send to client bid action + game state
-        let send_bid_action_json = json!({
-            "action": Action::Bid,
-            "bid": 0,
-            "hand": self.cards,
-            "state": game_state,
-        });
-        self.network_writer(&send_bid_action_json);
+        self.request(&Action::Bid(BidRequest {
+            hand: hand.to_vec(),
+            bids: bids.to_vec(),
+            next_bidder: seat,
+            forbidden_bid,
+            state: game_state.clone(),
+        }));
 
         // Receive bid from client
-        loop {
-            if let Ok((action, json)) = network_listener(&mut self.client_listener) {
-                if action == Action::Bid {
-                    self.bid = Some(json["bid"].as_u64().unwrap() as u8);
-                    return;
-                } else {
-                    eprintln!(
-                        "None bid action received during bidding phase. Action: {:?}",
-                        action
-                    );
-                }
-            }
+        match self.response()? {
+            ClientAction::Bid { bid } => Ok(bid),
+            action => bail!(
+                "None bid action received during bidding phase. Action: {:?}",
+                action
+            ),
         }
     }
 
-    pub fn play_card(&mut self, game_state: &Value) -> Card {
-        // Get playable cards
-        let playable_cards = self.playable_cards(game_state);
-
+    /// Ask the client which card to play
+    ///
+    /// # Errors
+    /// If the client disconnects, misses the deadline or responds with anything other than a played card
+    pub fn play_card(
+        &mut self,
+        hand: &[Card],
+        playable_cards: &[Card],
+        game_state: &GameState,
+    ) -> Result<Card> {
         // Send to client
-        let send_json = json!({
-            "action": Action::PlayCard,
-            "hand": self.cards,
-            "playable_cards": playable_cards,
-            "played_card": Value::Null,
-            "state": game_state,
-        });
-        self.network_writer(&send_json);
+        self.request(&Action::PlayCard(PlayCardRequest {
+            hand: hand.to_vec(),
+            playable_cards: playable_cards.to_vec(),
+            state: game_state.clone(),
+        }));
 
         // Receive played card from client
-        loop {
-            if let Ok((action, json)) = network_listener(&mut self.client_listener) {
-                if action == Action::PlayCard {
-                    let played_card = serde_json::from_value(json["played_card"].clone()).unwrap();
-
-                    // Remove played card from hand
-                    match self.cards.iter().position(|card| *card == played_card) {
-                        Some(index) => self.cards.remove(index),
-                        None => panic!(
-                            "Played card is not in player's hand. Card: {:?}; Hand: {:?}",
-                            played_card, self.cards
-                        ),
-                    };
-
-                    return played_card;
-                } else {
-                    eprintln!(
-                        "None PlayCard action received during card playing phase. Action: {:?}",
-                        action
-                    );
-                }
-            }
+        match self.response()? {
+            ClientAction::PlayCard { played_card } => Ok(played_card),
+            action => bail!(
+                "None PlayCard action received during card playing phase. Action: {:?}",
+                action
+            ),
         }
     }
 
-    /// List of playable cards given the current hand and what has been played
-    fn playable_cards(&self, game_state: &Value) -> Vec<Card> {
-        let mut playable_cards = Vec::new();
-        let lead_suit =
-            from_value::<Option<Suit>>(game_state.get("leading_suit").unwrap().clone()).unwrap();
-
-        let has_lead_suit = self.cards.iter().any(|card| {
-            if let Card::NormalCard(normal_card) = &card {
-                if Some(normal_card.suit) == lead_suit {
-                    return true;
-                }
-            }
-            false
-        });
-
-        // If hand does not contain lead suit, they can play whatever card
-        if !has_lead_suit {
-            return self.cards.clone();
-        }
+    /// Ask the client whether to raise or lower its bid after winning a trick with a Cloud
+    ///
+    /// # Errors
+    /// If the client disconnects, misses the deadline or responds with anything other than a bid adjustment
+    pub fn adjust_bid(&mut self, hand: &[Card], bid: u8, game_state: &GameState) -> Result<i8> {
+        self.request(&Action::AdjustBid(AdjustBidRequest {
+            hand: hand.to_vec(),
+            bid,
+            state: game_state.clone(),
+        }));
 
-        for card in self.cards.iter() {
-            match card {
-                Card::SpecialCard(_) => {
-                    playable_cards.push(card.clone());
-                }
-                Card::NormalCard(normal_card) => {
-                    if Some(normal_card.suit) == lead_suit {
-                        playable_cards.push(card.clone());
-                    }
-                }
-            };
+        match self.response()? {
+            ClientAction::AdjustBid { adjustment } => Ok(adjustment),
+            action => bail!(
+                "None AdjustBid action received after a Cloud trick. Action: {:?}",
+                action
+            ),
         }
-
-        playable_cards
     }
 
-    pub fn won_trick(&mut self) {
-        self.tricks_taken += 1;
-    }
+    /// Ask the client which card to pass to the left after a Juggler was played
+    ///
+    /// # Errors
+    /// If the client disconnects, misses the deadline or responds with anything other than a passed card
+    pub fn pass_card(&mut self, hand: &[Card], game_state: &GameState) -> Result<Card> {
+        self.request(&Action::PassCard(PassCardRequest {
+            hand: hand.to_vec(),
+            state: game_state.clone(),
+        }));
 
-    /// Update the score and reset bid and tricks_taken
-    /// # Panics
-    /// If a player has cards remaining or if the player does not have a bid yet.
-    pub fn update_score(&mut self) {
-        match self.bid {
-            Some(bid) => {
-                if self.tricks_taken == bid {
-                    self.score += 20 + (10 * self.tricks_taken as i16);
-                } else {
-                    self.score -= (bid as i8 - self.tricks_taken as i8).abs() as i16 * 10;
-                }
-            }
-            None => panic!("Cannot update score before having a bid!"),
-        }
-        self.bid = None;
-        self.tricks_taken = 0;
-        if self.cards.len() != 0 {
-            panic!("Cannot calculate score before all cards have been played!");
+        match self.response()? {
+            ClientAction::PassCard { card } => Ok(card),
+            action => bail!(
+                "None PassCard action received after a Juggler trick. Action: {:?}",
+                action
+            ),
         }
     }
 
     /// Choose trump in the case of a wizard being trump
-    pub fn choose_trump(&mut self, game_state: &Value) -> Suit {
+    ///
+    /// # Errors
+    /// If the client disconnects, misses the deadline or responds with anything other than a trump suit
+    pub fn choose_trump(&mut self, hand: &[Card], game_state: &GameState) -> Result<Suit> {
         // Send to client ChooseTrump action + game state
-        let send_choose_trump_action_json = json!({
-            "action": Action::ChooseTrump,
-            "trump": Value::Null,
-            "hand": self.cards,
-            "state": game_state,
-        });
-        self.network_writer(&send_choose_trump_action_json);
+        self.request(&Action::ChooseTrump(ChooseTrumpRequest {
+            hand: hand.to_vec(),
+            state: game_state.clone(),
+        }));
 
-        // Receive bid from client
-        loop {
-            if let Ok((action, json)) = network_listener(&mut self.client_listener) {
-                if action == Action::ChooseTrump {
-                    return serde_json::from_value(json["trump"].clone()).unwrap();
-                } else {
-                    eprintln!(
-                        "None ChooseTrump action received during trump choosing phase. Action: {:?}",
-                        action
-                    );
-                }
-            }
+        // Receive trump from client
+        match self.response()? {
+            ClientAction::ChooseTrump { trump } => Ok(trump),
+            action => bail!(
+                "None ChooseTrump action received during trump choosing phase. Action: {:?}",
+                action
+            ),
         }
     }
 }
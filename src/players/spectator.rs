@@ -0,0 +1,58 @@
+use crate::cards::card::Card;
+use crate::game::event::GameEvent;
+use crate::game::state::GameState;
+use crate::network::action::{Action, EndGameMessage, EventMessage};
+use crate::network::connection::Connection;
+
+/// Connection to a client watching a game without a seat
+///
+/// Spectators are sent every event of the game as it happens. Unless they are omniscient, the
+/// events are redacted so they never see a card in anyone's hand.
+pub struct Spectator {
+    name: String,
+    /// Sees every hand. For admins and for recording games that can be replayed.
+    omniscient: bool,
+    connection: Connection,
+}
+
+impl Spectator {
+    pub fn new(name: String, omniscient: bool, connection: Connection) -> Spectator {
+        Spectator {
+            name,
+            omniscient,
+            connection,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_omniscient(&self) -> bool {
+        self.omniscient
+    }
+
+    /// Tell the spectator about an event, along with the current state of the game
+    ///
+    /// # Arguments
+    ///
+    /// * `hands` - Every player's current hand. Only sent to omniscient spectators.
+    pub fn event(&mut self, event: &GameEvent, game_state: &GameState, hands: &[Vec<Card>]) {
+        let (event, hands) = if self.omniscient {
+            (event.clone(), Some(hands.to_vec()))
+        } else {
+            (event.redacted(), None)
+        };
+        self.connection.send(&Action::Event(EventMessage {
+            event,
+            state: game_state.clone(),
+            hands,
+        }));
+    }
+
+    pub fn end_game(&mut self, game_state: &GameState) {
+        self.connection.send(&Action::EndGame(EndGameMessage {
+            state: game_state.clone(),
+        }));
+    }
+}
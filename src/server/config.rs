@@ -0,0 +1,253 @@
+use crate::game::rules::GameRules;
+use crate::game::timeouts::TurnTimeouts;
+use anyhow::{Context, Result};
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Command line arguments for the server binary
+///
+/// Any argument that is given overrides the value from the config file.
+#[derive(Clone, Debug, Default, Parser)]
+#[command(about = "Host a game of Wizard", long_about = None)]
+pub struct ServerArgs {
+    /// TOML file to read the server config from
+    #[arg(short, long)]
+    pub config: Option<PathBuf>,
+    /// Address to accept connections on
+    #[arg(long)]
+    pub host: Option<String>,
+    /// Port to accept connections on
+    #[arg(short, long)]
+    pub port: Option<u16>,
+    /// Host a lobby where clients create and join any number of tables
+    #[arg(long)]
+    pub lobby: bool,
+    /// Number of players at the table
+    #[arg(short = 'n', long)]
+    pub players: Option<usize>,
+    /// Seed for shuffling. The same seed and player decisions replay the same game
+    #[arg(long)]
+    pub seed: Option<u64>,
+    /// File to write the game's event log to once the game ends
+    #[arg(long)]
+    pub event_log: Option<PathBuf>,
+    /// The last bidder may not make the total bids equal the number of cards dealt
+    #[arg(long)]
+    pub restricted_bidding: bool,
+    /// Every player bids at once without seeing the other bids
+    #[arg(long)]
+    pub blind_bidding: bool,
+    /// Seconds to wait for a disconnected player to rejoin before ending the game
+    #[arg(long)]
+    pub reconnect_timeout: Option<u64>,
+    /// Token spectators give to see every player's hand
+    #[arg(long)]
+    pub admin_token: Option<String>,
+    /// Milliseconds each player has for every decision before the server decides for them
+    #[arg(long)]
+    pub turn_timeout_ms: Option<u64>,
+}
+
+/// Server settings
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub host: String,
+    pub port: u16,
+    /// Host a lobby rather than a single game. Tables pick their own player count and rules.
+    pub lobby: bool,
+    pub players: usize,
+    /// Random when not set. Lobby tables add their table id to it.
+    pub seed: Option<u64>,
+    /// Event log is not written when not set
+    pub event_log: Option<PathBuf>,
+    /// Seconds to wait for a disconnected player to rejoin before ending the game
+    pub reconnect_timeout: u64,
+    /// Token spectators give to see every player's hand. Nobody can when not set.
+    pub admin_token: Option<String>,
+    /// Time limits for decisions, e.g. `[timeouts]` with `play_card_ms = 5000`
+    pub timeouts: TurnTimeouts,
+    /// Optional rules, e.g. `[rules]` with `restricted_bidding = true`. Without a `[rules.deck]`
+    /// every table gets the deck suited to its number of players.
+    pub rules: GameRules,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            host: "0.0.0.0".to_string(),
+            port: 7878,
+            lobby: false,
+            players: 3,
+            seed: None,
+            event_log: None,
+            reconnect_timeout: 300,
+            admin_token: None,
+            timeouts: TurnTimeouts::default(),
+            rules: GameRules::default(),
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Read a config file. Missing fields keep their default values.
+    pub fn from_file(path: &Path) -> Result<ServerConfig> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        toml::from_str(&contents).with_context(|| format!("Invalid config file {}", path.display()))
+    }
+
+    /// Build the config from the config file, if one was given, then apply command line overrides
+    pub fn from_args(args: ServerArgs) -> Result<ServerConfig> {
+        let mut config = match &args.config {
+            Some(path) => ServerConfig::from_file(path)?,
+            None => ServerConfig::default(),
+        };
+
+        if let Some(host) = args.host {
+            config.host = host;
+        }
+        if let Some(port) = args.port {
+            config.port = port;
+        }
+        if args.lobby {
+            config.lobby = true;
+        }
+        if let Some(players) = args.players {
+            config.players = players;
+        }
+        if args.seed.is_some() {
+            config.seed = args.seed;
+        }
+        if args.event_log.is_some() {
+            config.event_log = args.event_log;
+        }
+        if let Some(reconnect_timeout) = args.reconnect_timeout {
+            config.reconnect_timeout = reconnect_timeout;
+        }
+        if args.admin_token.is_some() {
+            config.admin_token = args.admin_token;
+        }
+        if let Some(ms) = args.turn_timeout_ms {
+            config.timeouts = TurnTimeouts::all(ms);
+        }
+        if args.restricted_bidding {
+            config.rules.restricted_bidding = true;
+        }
+        if args.blind_bidding {
+            config.rules.blind_bidding = true;
+        }
+
+        Ok(config)
+    }
+
+    /// Address the server listens on
+    pub fn address(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::deck::DeckSpec;
+    use crate::game::scoring::ScoringScheme;
+
+    #[test]
+    fn defaults() {
+        let config = ServerConfig::from_args(ServerArgs::parse_from(["server"])).unwrap();
+
+        assert_eq!(ServerConfig::default(), config);
+        assert_eq!("0.0.0.0:7878", config.address());
+    }
+
+    #[test]
+    fn args_override_config_file() {
+        let path = std::env::temp_dir().join(format!(
+            "wizard_server_config_test_{}.toml",
+            std::process::id()
+        ));
+        fs::write(&path, "port = 9000\nplayers = 5\n").unwrap();
+
+        let config = ServerConfig::from_args(ServerArgs::parse_from([
+            "server",
+            "--config",
+            path.to_str().unwrap(),
+            "-n",
+            "4",
+        ]))
+        .unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!("0.0.0.0", config.host);
+        assert_eq!(9000, config.port);
+        assert_eq!(4, config.players);
+    }
+
+    #[test]
+    fn restricted_bidding_from_config_file_or_args() {
+        let config: ServerConfig = toml::from_str("[rules]\nrestricted_bidding = true\n").unwrap();
+        assert!(config.rules.restricted_bidding);
+
+        let config =
+            ServerConfig::from_args(ServerArgs::parse_from(["server", "--restricted-bidding"]))
+                .unwrap();
+        assert!(config.rules.restricted_bidding);
+    }
+
+    #[test]
+    fn scoring_scheme_from_config_file() {
+        let config: ServerConfig = toml::from_str(
+            "[rules.scoring]\nscheme = \"ZeroBidBonus\"\nfrom_round = 10\nbonus = 30\n",
+        )
+        .unwrap();
+        assert_eq!(
+            ScoringScheme::ZeroBidBonus {
+                from_round: 10,
+                bonus: 30
+            },
+            config.rules.scoring
+        );
+    }
+
+    #[test]
+    fn deck_follows_player_count_unless_configured() {
+        let config =
+            ServerConfig::from_args(ServerArgs::parse_from(["server", "-n", "7"])).unwrap();
+        assert_eq!(None, config.rules.deck);
+        assert_eq!(DeckSpec::for_table(7), config.rules.deck_for(7));
+
+        let config: ServerConfig = toml::from_str(
+            "players = 7
+[rules.deck]
+wizards = 5
+",
+        )
+        .unwrap();
+        let deck = config.rules.deck_for(7);
+        assert_eq!(5, deck.wizards);
+        assert_eq!(4, deck.jesters);
+    }
+
+    #[test]
+    fn turn_timeouts_from_config_file_or_args() {
+        let config: ServerConfig = toml::from_str("[timeouts]\nbid_ms = 2000\n").unwrap();
+        assert_eq!(Some(2000), config.timeouts.bid_ms);
+        assert_eq!(None, config.timeouts.play_card_ms);
+
+        let config = ServerConfig::from_args(ServerArgs::parse_from([
+            "server",
+            "--turn-timeout-ms",
+            "500",
+        ]))
+        .unwrap();
+        assert_eq!(TurnTimeouts::all(500), config.timeouts);
+    }
+
+    #[test]
+    fn unknown_config_keys_are_rejected() {
+        assert!(toml::from_str::<ServerConfig>("player = 4").is_err());
+    }
+}
@@ -0,0 +1,555 @@
+use crate::game::engine::GameEngine;
+use crate::game::event::write_event_log;
+use crate::game::rules::GameRules;
+use crate::game::wizard::{Reconnection, WizardGame};
+use crate::network::action::{
+    Action, ClientAction, ConfirmationMessage, ErrorMessage, ErrorReason, TableInfo,
+};
+use crate::network::connection::Connection;
+use crate::network::network::{wait_for_incoming_connection, TimedOut};
+use crate::players::player::Player;
+use crate::players::spectator::Spectator;
+use crate::server::config::ServerConfig;
+use crate::server::server::{admit_spectator, new_session};
+use anyhow::{bail, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long a waiting table listens to each seated player before checking for new arrivals
+const TABLE_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Lobby table that is waiting for players or playing its game
+struct Table {
+    info: TableInfo,
+    /// Hands players who sat down to the thread hosting the table
+    seats: Sender<Player>,
+    /// Hands spectators to the table's game, which sends them everything they missed
+    spectators: Sender<Spectator>,
+}
+
+/// Long running server hosting any number of tables at once
+///
+/// Clients join the lobby, then list, create, join and leave tables. Once every seat at a table
+/// is taken its game is played on the table's own thread. The server config's rules and player
+/// count are not used. Each table brings its own.
+pub struct Lobby {
+    config: ServerConfig,
+    tables: Mutex<BTreeMap<u64, Table>>,
+    /// Session of every player in a game being played, with their seat and where to send them
+    /// when they rejoin
+    sessions: Mutex<HashMap<String, (usize, Sender<Reconnection>)>>,
+    last_table: AtomicU64,
+}
+
+impl Lobby {
+    pub fn new(config: ServerConfig) -> Lobby {
+        Lobby {
+            config,
+            tables: Mutex::new(BTreeMap::new()),
+            sessions: Mutex::new(HashMap::new()),
+            last_table: AtomicU64::new(0),
+        }
+    }
+
+    /// Accept clients until the process is stopped
+    pub fn run(self) -> Result<()> {
+        let listener = TcpListener::bind(self.config.address())?;
+        println!("Lobby running on {}", self.config.address());
+
+        let lobby = Arc::new(self);
+        loop {
+            match wait_for_incoming_connection(&listener) {
+                Ok(stream) => {
+                    let lobby = Arc::clone(&lobby);
+                    thread::spawn(move || {
+                        if let Err(e) = lobby.welcome(stream) {
+                            eprintln!("Client left the lobby: {}", e);
+                        }
+                    });
+                }
+                Err(e) => eprintln!("Failed to accept client: {}", e),
+            }
+        }
+    }
+
+    /// Tables in the lobby, ordered by id
+    fn table_list(&self) -> Vec<TableInfo> {
+        let tables = self.tables.lock().unwrap();
+        tables.values().map(|table| table.info.clone()).collect()
+    }
+
+    /// Let a new client into the lobby, or seat a rejoining client back at its game
+    ///
+    /// This is a blocking operation
+    fn welcome(self: &Arc<Self>, stream: TcpStream) -> Result<()> {
+        let mut connection = Connection::new(stream)?;
+
+        match connection.receive()? {
+            ClientAction::Join {
+                name,
+                session: None,
+            } => {
+                let session = new_session();
+                connection.send(&Action::Confirmation(ConfirmationMessage {
+                    msg: format!("Welcome {}", name),
+                    session: Some(session.clone()),
+                }));
+                self.serve(Player::new(name, session, connection))
+            }
+            ClientAction::Join {
+                name,
+                session: Some(session),
+            } => self.rejoin(name, session, connection),
+            ClientAction::Spectate {
+                name,
+                table,
+                admin_token,
+            } => self.watch(name, table, admin_token, connection),
+            action => bail!("Expected client to join. Action: {:?}", action),
+        }
+    }
+
+    /// Hand a client that rejoined with its session to the game it was playing
+    fn rejoin(&self, name: String, session: String, mut connection: Connection) -> Result<()> {
+        let seat = self.sessions.lock().unwrap().get(&session).cloned();
+        let Some((seat, game)) = seat else {
+            connection.send(&Action::Error(ErrorMessage {
+                reason: ErrorReason::UnexpectedAction,
+                msg: "Session is not playing a game".to_string(),
+            }));
+            bail!("{} tried to rejoin with an unknown session", name);
+        };
+
+        connection.send(&Action::Confirmation(ConfirmationMessage {
+            msg: format!("Welcome back {}", name),
+            session: Some(session),
+        }));
+        if game.send(Reconnection { seat, connection }).is_err() {
+            bail!("Game of {} ended before they rejoined", name);
+        }
+
+        Ok(())
+    }
+
+    /// Let a client watch a table's game, from the start even if it is already being played
+    fn watch(
+        &self,
+        name: String,
+        table: Option<u64>,
+        admin_token: Option<String>,
+        mut connection: Connection,
+    ) -> Result<()> {
+        let spectators = table.and_then(|table| {
+            let tables = self.tables.lock().unwrap();
+            tables.get(&table).map(|table| table.spectators.clone())
+        });
+        let Some(spectators) = spectators else {
+            connection.send(&Action::Error(ErrorMessage {
+                reason: ErrorReason::TableUnavailable,
+                msg: format!("There is no table {:?} to watch", table),
+            }));
+            bail!("{} tried to watch missing table {:?}", name, table);
+        };
+
+        let spectator = admit_spectator(
+            connection,
+            name,
+            admin_token,
+            self.config.admin_token.as_deref(),
+        )?;
+        if spectators.send(spectator).is_err() {
+            bail!("Table {:?} closed before it could be watched", table);
+        }
+
+        Ok(())
+    }
+
+    /// Answer a player's lobby requests until they sit down at a table
+    fn serve(self: &Arc<Self>, mut player: Player) -> Result<()> {
+        loop {
+            let table = match player.lobby_action(None)? {
+                ClientAction::ListTables => {
+                    player.tables(&self.table_list());
+                    continue;
+                }
+                ClientAction::CreateTable { num_players, rules } => {
+                    match self.create_table(num_players, rules) {
+                        Ok(table) => table,
+                        Err(e) => {
+                            player.error(ErrorMessage {
+                                reason: ErrorReason::InvalidTable,
+                                msg: e.to_string(),
+                            });
+                            continue;
+                        }
+                    }
+                }
+                ClientAction::JoinTable { table } => table,
+                action => {
+                    player.error(ErrorMessage {
+                        reason: ErrorReason::UnexpectedAction,
+                        msg: format!("Not sitting at a table. Action: {:?}", action),
+                    });
+                    continue;
+                }
+            };
+
+            player = match self.sit_down(table, player) {
+                None => return Ok(()),
+                Some(player) => player,
+            };
+        }
+    }
+
+    /// Open a table and start the thread hosting it
+    ///
+    /// # Errors
+    /// If a game can not be played with `num_players` and `rules`
+    fn create_table(self: &Arc<Self>, num_players: usize, rules: GameRules) -> Result<u64> {
+        GameEngine::with_rules(num_players, 0, rules.clone())?;
+
+        let id = self.last_table.fetch_add(1, Ordering::Relaxed) + 1;
+        let (seats, arrivals) = mpsc::channel();
+        let (spectators, spectator_arrivals) = mpsc::channel();
+        let info = TableInfo {
+            id,
+            num_players,
+            players: Vec::new(),
+            rules,
+            started: false,
+        };
+        self.tables.lock().unwrap().insert(
+            id,
+            Table {
+                info,
+                seats,
+                spectators,
+            },
+        );
+        println!("Table {} opened for {} players", id, num_players);
+
+        let lobby = Arc::clone(self);
+        thread::spawn(move || {
+            if let Err(e) = lobby.host_table(id, arrivals, spectator_arrivals) {
+                eprintln!("Table {} failed: {}", id, e);
+            }
+        });
+
+        Ok(id)
+    }
+
+    /// Seat a player at a table that still has room
+    ///
+    /// Hands the player back, after telling them why, if the table is missing, full or already
+    /// playing
+    fn sit_down(&self, id: u64, mut player: Player) -> Option<Player> {
+        let mut tables = self.tables.lock().unwrap();
+        let msg = match tables.get_mut(&id) {
+            None => format!("There is no table {}", id),
+            Some(table) if table.info.players.len() == table.info.num_players => {
+                format!("Table {} is full", id)
+            }
+            Some(table) => {
+                table.info.players.push(player.name().to_string());
+                player.table_joined(&table.info);
+                match table.seats.send(player) {
+                    Ok(()) => return None,
+                    Err(returned) => {
+                        table.info.players.pop();
+                        player = returned.0;
+                        format!("Table {} is closed", id)
+                    }
+                }
+            }
+        };
+
+        player.error(ErrorMessage {
+            reason: ErrorReason::TableUnavailable,
+            msg,
+        });
+        Some(player)
+    }
+
+    /// Remove the player in `seat` from the table, closing the table once it is empty
+    ///
+    /// Returns whether the table was closed
+    fn stand_up(&self, id: u64, seat: usize) -> bool {
+        let mut tables = self.tables.lock().unwrap();
+        let Some(table) = tables.get_mut(&id) else {
+            return true;
+        };
+
+        table.info.players.remove(seat);
+        if table.info.players.is_empty() {
+            tables.remove(&id);
+            println!("Table {} closed", id);
+            return true;
+        }
+
+        false
+    }
+
+    /// Wait for a table to fill up, then play its game
+    ///
+    /// While waiting, seated players may list the tables or leave. Players who leave go back to
+    /// the lobby.
+    fn host_table(
+        self: Arc<Self>,
+        id: u64,
+        arrivals: Receiver<Player>,
+        spectators: Receiver<Spectator>,
+    ) -> Result<()> {
+        let num_players = self.tables.lock().unwrap()[&id].info.num_players;
+        let mut players: Vec<Player> = Vec::new();
+
+        while players.len() < num_players {
+            if players.is_empty() {
+                if let Ok(player) = arrivals.recv_timeout(TABLE_POLL_INTERVAL) {
+                    players.push(player);
+                }
+            }
+            players.extend(arrivals.try_iter());
+
+            let mut seat = 0;
+            while seat < players.len() && players.len() < num_players {
+                let deadline = Some(Instant::now() + TABLE_POLL_INTERVAL);
+                let left = match players[seat].lobby_action(deadline) {
+                    Err(e) if e.is::<TimedOut>() => false,
+                    Ok(ClientAction::ListTables) => {
+                        let tables = self.table_list();
+                        players[seat].tables(&tables);
+                        false
+                    }
+                    Ok(ClientAction::LeaveTable) => true,
+                    Ok(action) => {
+                        players[seat].error(ErrorMessage {
+                            reason: ErrorReason::UnexpectedAction,
+                            msg: format!("Waiting for the table to fill. Action: {:?}", action),
+                        });
+                        false
+                    }
+                    Err(e) => {
+                        eprintln!("{} left table {}: {}", players[seat].name(), id, e);
+                        true
+                    }
+                };
+                if !left {
+                    seat += 1;
+                    continue;
+                }
+
+                let mut player = players.remove(seat);
+                let closed = self.stand_up(id, seat);
+                player.table_left(id);
+                let lobby = Arc::clone(&self);
+                thread::spawn(move || {
+                    if let Err(e) = lobby.serve(player) {
+                        eprintln!("Client left the lobby: {}", e);
+                    }
+                });
+                if closed {
+                    return Ok(());
+                }
+            }
+        }
+
+        let rules = {
+            let mut tables = self.tables.lock().unwrap();
+            let table = tables
+                .get_mut(&id)
+                .expect("Table is open until its game ends");
+            table.info.started = true;
+            table.info.rules.clone()
+        };
+        println!("Table {} is full. Starting the game", id);
+
+        let sessions: Vec<String> = players.iter().map(|p| p.session().to_string()).collect();
+        let result = self.play_table(id, players, spectators, rules, &sessions);
+
+        // The table and its sessions go away whether or not the game finished
+        let mut open_sessions = self.sessions.lock().unwrap();
+        for session in sessions.iter() {
+            open_sessions.remove(session);
+        }
+        self.tables.lock().unwrap().remove(&id);
+        println!("Table {} closed", id);
+
+        result
+    }
+
+    /// Play a full table's game
+    fn play_table(
+        &self,
+        id: u64,
+        players: Vec<Player>,
+        spectators: Receiver<Spectator>,
+        rules: GameRules,
+        sessions: &[String],
+    ) -> Result<()> {
+        let (reconnections, receiver) = mpsc::channel();
+        {
+            let mut open_sessions = self.sessions.lock().unwrap();
+            for (seat, session) in sessions.iter().enumerate() {
+                open_sessions.insert(session.clone(), (seat, reconnections.clone()));
+            }
+        }
+
+        let mut game = WizardGame::new(
+            players.len(),
+            players,
+            table_seed(self.config.seed, id),
+            rules,
+        )?;
+        game.set_turn_timeouts(self.config.timeouts);
+        game.accept_reconnections(receiver, Duration::from_secs(self.config.reconnect_timeout));
+        game.accept_spectators(spectators);
+        let result = game.play_game();
+
+        // Write the log even if the game failed so it can be inspected
+        if let Some(path) = &self.config.event_log {
+            let path = table_event_log(path, id);
+            write_event_log(&path, game.events())?;
+            println!("Event log for table {} written to {}", id, path.display());
+        }
+
+        result
+    }
+}
+
+/// Seed of a lobby table's game, so tables deal differently but still replay from the server seed
+///
+/// Without a server seed every table draws its own random seed.
+fn table_seed(seed: Option<u64>, table: u64) -> Option<u64> {
+    seed.map(|seed| seed.wrapping_add(table))
+}
+
+/// Event log file of a lobby table, e.g. `games.jsonl` becomes `games-table3.jsonl`
+fn table_event_log(path: &Path, table: u64) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(extension) => format!("{}-table{}.{}", stem, table, extension.to_string_lossy()),
+        None => format!("{}-table{}", stem, table),
+    };
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::client::Client;
+    use crate::client::random_client::RandomClient;
+    use crate::game::rules::RoundSchedule;
+
+    fn start_lobby(port: u16) {
+        let config = ServerConfig {
+            port,
+            lobby: true,
+            ..Default::default()
+        };
+        thread::spawn(move || Lobby::new(config).run().unwrap());
+        thread::sleep(Duration::from_millis(100));
+    }
+
+    #[test]
+    fn tables_get_their_own_seeds() {
+        assert_eq!(None, table_seed(None, 1));
+        assert_eq!(Some(11), table_seed(Some(10), 1));
+        assert_eq!(Some(12), table_seed(Some(10), 2));
+        assert_eq!(Some(0), table_seed(Some(u64::MAX), 1));
+    }
+
+    #[test]
+    fn tables_are_created_listed_and_left() {
+        start_lobby(7882);
+        let mut lobby = Connection::connect("0.0.0.0", "7882").unwrap();
+        lobby.send(&ClientAction::Join {
+            name: "alice".to_string(),
+            session: None,
+        });
+        assert!(matches!(lobby.receive().unwrap(), Action::Confirmation(_)));
+
+        lobby.send(&ClientAction::JoinTable { table: 99 });
+        let Action::Error(error) = lobby.receive().unwrap() else {
+            panic!("Expected error");
+        };
+        assert_eq!(ErrorReason::TableUnavailable, error.reason);
+
+        lobby.send(&ClientAction::CreateTable {
+            num_players: 1,
+            rules: GameRules::default(),
+        });
+        let Action::Error(error) = lobby.receive().unwrap() else {
+            panic!("Expected error");
+        };
+        assert_eq!(ErrorReason::InvalidTable, error.reason);
+
+        lobby.send(&ClientAction::CreateTable {
+            num_players: 2,
+            rules: GameRules::default(),
+        });
+        let Action::TableJoined(joined) = lobby.receive().unwrap() else {
+            panic!("Expected to sit down");
+        };
+        assert_eq!(vec!["alice".to_string()], joined.table.players);
+
+        lobby.send(&ClientAction::ListTables);
+        let Action::Tables(list) = lobby.receive().unwrap() else {
+            panic!("Expected tables");
+        };
+        assert_eq!(vec![joined.table.clone()], list.tables);
+
+        // The table closes once its last player leaves
+        lobby.send(&ClientAction::LeaveTable);
+        let Action::TableLeft(left) = lobby.receive().unwrap() else {
+            panic!("Expected to leave");
+        };
+        assert_eq!(joined.table.id, left.table);
+        lobby.send(&ClientAction::ListTables);
+        let Action::Tables(list) = lobby.receive().unwrap() else {
+            panic!("Expected tables");
+        };
+        assert!(list.tables.is_empty());
+    }
+
+    #[test]
+    fn tables_play_at_the_same_time() {
+        start_lobby(7883);
+        let rules = GameRules {
+            rounds: RoundSchedule::Short { rounds: 3 },
+            ..Default::default()
+        };
+
+        let mut client_threads = Vec::new();
+        let tables = [
+            ClientAction::CreateTable {
+                num_players: 2,
+                rules: rules.clone(),
+            },
+            ClientAction::CreateTable {
+                num_players: 3,
+                rules,
+            },
+            ClientAction::JoinTable { table: 1 },
+            ClientAction::JoinTable { table: 2 },
+            ClientAction::JoinTable { table: 2 },
+        ];
+        for table in tables {
+            client_threads.push(thread::spawn(move || {
+                RandomClient::new()
+                    .lobby_client("0.0.0.0", "7883", "random", Some(table))
+                    .unwrap()
+            }));
+            thread::sleep(Duration::from_millis(100));
+        }
+
+        for client_thread in client_threads {
+            client_thread.join().unwrap();
+        }
+    }
+}
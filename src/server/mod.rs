@@ -1 +1,4 @@
+pub mod config;
+pub mod lobby;
+#[allow(clippy::module_inception)]
 pub mod server;
@@ -1,92 +1,295 @@
-use crate::game::wizard::WizardGame;
-use crate::network::action::Action;
-use crate::network::network::{network_listener, network_writer, wait_for_incoming_connection};
-use serde_json::json;
+use crate::game::event::write_event_log;
+use crate::game::wizard::{Reconnection, WizardGame};
+use crate::network::action::{
+    Action, ClientAction, ConfirmationMessage, ErrorMessage, ErrorReason,
+};
+use crate::network::connection::Connection;
+use crate::network::network::wait_for_incoming_connection;
+use crate::players::player::Player;
+use crate::players::spectator::Spectator;
+use crate::server::config::ServerConfig;
+use anyhow::{bail, Result};
+use std::io::ErrorKind;
 use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Arc;
 use std::thread;
+use std::time::Duration;
 
-pub struct Server;
+/// How often the listener checks whether the game is over while waiting for clients to rejoin
+const REJOIN_POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// How long a connecting client has to say whether it joins, rejoins or watches
+const JOIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Token a client rejoins its seat with after losing its connection
+pub fn new_session() -> String {
+    format!("{:032x}", rand::random::<u128>())
+}
+
+/// Let a client watch the game, seeing every hand if it gave the server's admin token
+///
+/// # Errors
+/// If the client gave an admin token that is not `server_token`
+pub fn admit_spectator(
+    mut connection: Connection,
+    name: String,
+    admin_token: Option<String>,
+    server_token: Option<&str>,
+) -> Result<Spectator> {
+    let omniscient = admin_token.is_some();
+    if omniscient && admin_token.as_deref() != server_token {
+        connection.send(&Action::Error(ErrorMessage {
+            reason: ErrorReason::NotAuthorized,
+            msg: "Wrong admin token".to_string(),
+        }));
+        bail!("{} tried to watch with the wrong admin token", name);
+    }
+
+    connection.send(&Action::Confirmation(ConfirmationMessage {
+        msg: format!("Welcome {}. You are watching", name),
+        session: None,
+    }));
+    Ok(Spectator::new(name, omniscient, connection))
+}
+
+pub struct Server {
+    config: ServerConfig,
+}
 
 impl Server {
-    /// Connect a write connection to the client
+    pub fn new(config: ServerConfig) -> Server {
+        Server { config }
+    }
+
+    /// Wait for a new client to connect and join the game
     ///
-    /// This is a blocking function that waits for the client to send a Action::Connect over the
-    /// given stream
-    fn create_client_write_connection(client_read_stream: &mut TcpStream) -> TcpStream {
-        loop {
-            if let Ok((action, json)) = network_listener(client_read_stream) {
-                if action == Action::Connect {
-                    let host: String = serde_json::from_value(json["host"].clone()).unwrap();
-                    let port = json["port"].as_u64().unwrap();
-                    let mut client_write_stream =
-                        TcpStream::connect(format!("{}:{}", host, port)).unwrap();
-
-                    // Send message to client confirming the connection
-                    let confirmation_msg = json!({
-                        "action": Action::Confirmation,
-                        "msg": "Server write connection established",
-                    });
-                    let serialized = serde_json::to_vec(&confirmation_msg).unwrap();
-
-                    network_writer(&mut client_write_stream, serialized);
-
-                    println!("Client connected on Port: {}", port);
-                    return client_write_stream;
+    /// This is a blocking operation. Clients that came to watch are sent to `spectators` and
+    /// `None` is returned.
+    ///
+    /// # Errors
+    /// If the client does not join within `JOIN_TIMEOUT`, so a silent client can not keep others
+    /// from joining
+    fn accept_player(
+        listener: &TcpListener,
+        spectators: &Sender<Spectator>,
+        admin_token: Option<&str>,
+    ) -> Result<Option<Player>> {
+        let stream = wait_for_incoming_connection(listener)?;
+        let socket = stream.try_clone()?;
+        socket.set_read_timeout(Some(JOIN_TIMEOUT))?;
+        let mut connection = Connection::new(stream)?;
+
+        let message = connection.receive();
+        socket.set_read_timeout(None)?;
+        let name = match message? {
+            ClientAction::Join { name, .. } => name,
+            ClientAction::Spectate {
+                name,
+                admin_token: token,
+                ..
+            } => {
+                let spectator = admit_spectator(connection, name, token, admin_token)?;
+                println!("{} is waiting to watch", spectator.name());
+                spectators.send(spectator)?;
+                return Ok(None);
+            }
+            action => bail!("Expected client to join. Action: {:?}", action),
+        };
+
+        // Send message to client confirming the connection
+        let session = new_session();
+        connection.send(&Action::Confirmation(ConfirmationMessage {
+            msg: format!("Welcome {}", name),
+            session: Some(session.clone()),
+        }));
+
+        Ok(Some(Player::new(name, session, connection)))
+    }
+
+    /// Seat a client rejoining the game in progress with the session it was given
+    ///
+    /// This is a blocking operation. Clients that came to watch are sent to `spectators` and
+    /// `None` is returned.
+    fn accept_rejoin(
+        stream: TcpStream,
+        sessions: &[String],
+        spectators: &Sender<Spectator>,
+        admin_token: Option<&str>,
+    ) -> Result<Option<Reconnection>> {
+        // Don't let a silent client keep the listener from noticing the game is over
+        let socket = stream.try_clone()?;
+        socket.set_read_timeout(Some(JOIN_TIMEOUT))?;
+        let mut connection = Connection::new(stream)?;
+
+        let (name, seat) = match connection.receive()? {
+            ClientAction::Join {
+                name,
+                session: Some(session),
+            } => (name, sessions.iter().position(|s| *s == session)),
+            ClientAction::Spectate {
+                name,
+                admin_token: token,
+                ..
+            } => {
+                socket.set_read_timeout(None)?;
+                spectators.send(admit_spectator(connection, name, token, admin_token)?)?;
+                return Ok(None);
+            }
+            action => (format!("{:?}", action), None),
+        };
+        let Some(seat) = seat else {
+            connection.send(&Action::Error(ErrorMessage {
+                reason: ErrorReason::UnexpectedAction,
+                msg: "Game already started. Only players with a session may rejoin".to_string(),
+            }));
+            bail!("Client without a valid session tried to join: {}", name);
+        };
+        socket.set_read_timeout(None)?;
+
+        connection.send(&Action::Confirmation(ConfirmationMessage {
+            msg: format!("Welcome back {}", name),
+            session: Some(sessions[seat].clone()),
+        }));
+
+        Ok(Some(Reconnection { seat, connection }))
+    }
+
+    /// Hand clients that rejoin with a valid session, and spectators, to the game until `stop`
+    /// is set
+    fn listen_for_rejoins(
+        listener: TcpListener,
+        sessions: Vec<String>,
+        sender: Sender<Reconnection>,
+        spectators: Sender<Spectator>,
+        admin_token: Option<String>,
+        stop: Arc<AtomicBool>,
+    ) -> Result<()> {
+        listener.set_nonblocking(true)?;
+        while !stop.load(Ordering::Relaxed) {
+            let stream = match listener.accept() {
+                Ok((stream, addr)) => {
+                    println!("Client rejoining: {addr:?}");
+                    stream
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                    thread::sleep(REJOIN_POLL_INTERVAL);
+                    continue;
+                }
+                Err(e) => {
+                    eprintln!("Failed to accept client: {}", e);
+                    continue;
                 }
+            };
+
+            stream.set_nonblocking(false)?;
+            match Server::accept_rejoin(stream, &sessions, &spectators, admin_token.as_deref()) {
+                // The game is over once it stops receiving
+                Ok(Some(reconnection)) => {
+                    if sender.send(reconnection).is_err() {
+                        break;
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => eprintln!("Failed to rejoin client: {}", e),
             }
         }
+
+        Ok(())
     }
 
-    pub fn start_server(&mut self, num_players: usize) {
-        let listener = TcpListener::bind("0.0.0.0:7878").unwrap();
-        println!("Server running");
+    pub fn start_server(&mut self) -> Result<()> {
+        let num_players = self.config.players;
+        let listener = TcpListener::bind(self.config.address())?;
+        println!("Server running on {}", self.config.address());
 
         // Wait for players to connect
-        let mut player_read_streams = Vec::new();
-        let mut player_write_streams = Vec::new();
+        let mut players = Vec::new();
+        let (spectators, spectator_arrivals) = mpsc::channel();
+        let admin_token = self.config.admin_token.clone();
 
         println!("Waiting for players to connect");
-        while player_read_streams.len() != num_players {
-            if let Ok(stream) = wait_for_incoming_connection(&listener) {
-                player_read_streams.push(stream);
-                println!(
-                    "Player {} of {} connected!",
-                    player_read_streams.len(),
-                    num_players
-                );
+        while players.len() != num_players {
+            match Server::accept_player(&listener, &spectators, admin_token.as_deref()) {
+                Ok(None) => {}
+                Ok(Some(player)) => {
+                    println!(
+                        "Player {} of {} connected: {}",
+                        players.len() + 1,
+                        num_players,
+                        player.name()
+                    );
+                    players.push(player);
+                }
+                Err(e) => eprintln!("Failed to accept client: {}", e),
             }
         }
 
-        // Create write connection to clients
-        for mut player_read_stream in player_read_streams.iter_mut() {
-            player_write_streams.push(Server::create_client_write_connection(
-                &mut player_read_stream,
-            ));
-        }
+        // Keep accepting connections so dropped players can rejoin their seat
+        let sessions = players.iter().map(|p| p.session().to_string()).collect();
+        let (sender, receiver) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let rejoin_thread = {
+            let stop = Arc::clone(&stop);
+            thread::spawn(move || {
+                Server::listen_for_rejoins(
+                    listener,
+                    sessions,
+                    sender,
+                    spectators,
+                    admin_token,
+                    stop,
+                )
+            })
+        };
 
         // Start game
-        let mut game =
-            WizardGame::new(num_players, player_read_streams, player_write_streams).unwrap();
-        thread::sleep(std::time::Duration::from_millis(100)); // Delay helps clients connect to
-                                                              // server properly
-        game.play_game();
+        let mut game = WizardGame::new(
+            num_players,
+            players,
+            self.config.seed,
+            self.config.rules.clone(),
+        )?;
+        game.set_turn_timeouts(self.config.timeouts);
+        game.accept_reconnections(receiver, Duration::from_secs(self.config.reconnect_timeout));
+        game.accept_spectators(spectator_arrivals);
+        let result = game.play_game();
+
+        stop.store(true, Ordering::Relaxed);
+        if let Err(e) = rejoin_thread.join().expect("Rejoin listener panicked") {
+            eprintln!("Stopped accepting rejoining clients: {}", e);
+        }
+
+        // Write the log even if the game failed so it can be inspected
+        if let Some(path) = &self.config.event_log {
+            write_event_log(path, game.events())?;
+            println!("Event log written to {}", path.display());
+        }
+
+        result
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cards::suit::Suit;
     use crate::client::client::Client;
     use crate::client::random_client::RandomClient;
-    //use std::io::Read;
+    use crate::client::spectator_client::spectate;
+    use crate::game::engine::{GameEngine, Turn};
+    use crate::game::event::{read_event_log, GameEvent};
+    use crate::game::rules::{GameRules, RoundSchedule};
+    use crate::game::timeouts::TurnTimeouts;
 
-    #[test]
-    fn full_game_with_3_clients() {
-        let num_players = 4;
+    /// Host a game with `config` and play it to the end with random clients
+    fn play_with_random_clients(config: ServerConfig) {
+        let num_players = config.players;
+        let port = config.port.to_string();
         // Start server
         let server_thread = thread::spawn(move || {
-            let mut server = Server {};
-            server.start_server(num_players);
+            let mut server = Server::new(config);
+            server.start_server().unwrap();
         });
 
         // Let the server start up
@@ -96,9 +299,10 @@ mod tests {
         let mut client_threads = Vec::new();
         for _ in 0..num_players {
             thread::sleep(std::time::Duration::from_millis(100));
+            let port = port.clone();
             client_threads.push(thread::spawn(move || {
                 let mut client = RandomClient::new();
-                if let Err(e) = client.client("0.0.0.0", "7878") {
+                if let Err(e) = client.client("0.0.0.0", &port, "random") {
                     panic!("Error occurred: {}", e);
                 }
             }));
@@ -112,4 +316,247 @@ mod tests {
         }
         println!("client Joined");
     }
+
+    #[test]
+    fn full_game_with_3_clients() {
+        let event_log = std::env::temp_dir().join(format!(
+            "wizard_server_event_log_test_{}.jsonl",
+            std::process::id()
+        ));
+        play_with_random_clients(ServerConfig {
+            players: 4,
+            event_log: Some(event_log.clone()),
+            ..Default::default()
+        });
+
+        // The logged game can be replayed to the end
+        let events = read_event_log(&event_log).unwrap();
+        std::fs::remove_file(&event_log).unwrap();
+        assert_eq!(Turn::GameOver, GameEngine::replay(&events).unwrap().turn());
+    }
+
+    #[test]
+    fn blind_bidding_game() {
+        play_with_random_clients(ServerConfig {
+            port: 7879,
+            players: 3,
+            rules: GameRules {
+                blind_bidding: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+    }
+
+    /// Join as `name`, drop the connection on the first request, then rejoin and play to the end
+    fn drop_and_rejoin(port: &str) {
+        let mut server = Connection::connect("0.0.0.0", port).unwrap();
+        server.send(&ClientAction::Join {
+            name: "flaky".to_string(),
+            session: None,
+        });
+        let Action::Confirmation(confirmation) = server.receive().unwrap() else {
+            panic!("Expected confirmation");
+        };
+        loop {
+            match server.receive().unwrap() {
+                Action::Bid(_) | Action::ChooseTrump(_) | Action::PlayCard(_) => break,
+                _ => {}
+            }
+        }
+        drop(server);
+
+        let mut server = Connection::connect("0.0.0.0", port).unwrap();
+        server.send(&ClientAction::Join {
+            name: "flaky".to_string(),
+            session: confirmation.session.clone(),
+        });
+        let Action::Confirmation(rejoined) = server.receive().unwrap() else {
+            panic!("Expected confirmation");
+        };
+        assert_eq!(confirmation.session, rejoined.session);
+        let Action::Resume(resume) = server.receive().unwrap() else {
+            panic!("Expected resume");
+        };
+        assert_eq!(resume.hand.len(), resume.state.hand_size as usize);
+
+        loop {
+            let response = match server.receive().unwrap() {
+                Action::AdjustBid(_) => ClientAction::AdjustBid { adjustment: 1 },
+                Action::Bid(request) => ClientAction::Bid {
+                    bid: match request.forbidden_bid {
+                        Some(0) => 1,
+                        _ => 0,
+                    },
+                },
+                Action::ChooseTrump(_) => ClientAction::ChooseTrump { trump: Suit::Club },
+                Action::PassCard(request) => ClientAction::PassCard {
+                    card: request.hand[0],
+                },
+                Action::PlayCard(request) => ClientAction::PlayCard {
+                    played_card: request.playable_cards[0].with_chosen_suit(Suit::Club),
+                },
+                Action::EndGame(_) => break,
+                _ => continue,
+            };
+            server.send(&response);
+        }
+    }
+
+    #[test]
+    fn dropped_player_rejoins_their_seat() {
+        let config = ServerConfig {
+            port: 7880,
+            players: 3,
+            rules: GameRules {
+                rounds: RoundSchedule::Short { rounds: 3 },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let server_thread = thread::spawn(move || Server::new(config).start_server().unwrap());
+        thread::sleep(Duration::from_millis(100));
+
+        let flaky_thread = thread::spawn(|| drop_and_rejoin("7880"));
+        let mut client_threads = Vec::new();
+        for _ in 0..2 {
+            thread::sleep(Duration::from_millis(100));
+            client_threads.push(thread::spawn(|| {
+                RandomClient::new()
+                    .client("0.0.0.0", "7880", "random")
+                    .unwrap()
+            }));
+        }
+
+        server_thread.join().unwrap();
+        flaky_thread.join().unwrap();
+        for client_thread in client_threads {
+            client_thread.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn silent_client_does_not_keep_others_from_joining() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let (spectators, _) = mpsc::channel();
+
+        let _silent = TcpStream::connect(address).unwrap();
+        let mut joining = Connection::new(TcpStream::connect(address).unwrap()).unwrap();
+        joining.send(&ClientAction::Join {
+            name: "joining".to_string(),
+            session: None,
+        });
+
+        assert!(Server::accept_player(&listener, &spectators, None).is_err());
+        let player = Server::accept_player(&listener, &spectators, None).unwrap();
+        assert_eq!("joining", player.unwrap().name());
+    }
+
+    #[test]
+    fn stalled_player_is_decided_for() {
+        let config = ServerConfig {
+            port: 7881,
+            players: 3,
+            timeouts: TurnTimeouts::all(500),
+            rules: GameRules {
+                rounds: RoundSchedule::Short { rounds: 2 },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let server_thread = thread::spawn(move || Server::new(config).start_server().unwrap());
+        thread::sleep(Duration::from_millis(100));
+
+        // Never answers a request
+        let stalled_thread = thread::spawn(|| {
+            let mut server = Connection::connect("0.0.0.0", "7881").unwrap();
+            server.send(&ClientAction::Join {
+                name: "stalled".to_string(),
+                session: None,
+            });
+
+            let mut seat = None;
+            let mut timeouts = 0;
+            loop {
+                match server.receive().unwrap() {
+                    Action::StartGame(start) => {
+                        seat = start.state.players.iter().position(|p| p.name == "stalled");
+                    }
+                    Action::TurnTimedOut(timed_out) if Some(timed_out.player) == seat => {
+                        timeouts += 1;
+                    }
+                    Action::EndGame(_) => break,
+                    _ => {}
+                }
+            }
+            timeouts
+        });
+        let mut client_threads = Vec::new();
+        for _ in 0..2 {
+            thread::sleep(Duration::from_millis(100));
+            client_threads.push(thread::spawn(|| {
+                RandomClient::new()
+                    .client("0.0.0.0", "7881", "random")
+                    .unwrap()
+            }));
+        }
+
+        server_thread.join().unwrap();
+        // A bid and a card in the first round, a bid and two cards in the second
+        assert!(stalled_thread.join().unwrap() >= 5);
+        for client_thread in client_threads {
+            client_thread.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn spectators_only_see_hands_with_the_admin_token() {
+        let config = ServerConfig {
+            port: 7884,
+            players: 3,
+            admin_token: Some("secret".to_string()),
+            rules: GameRules {
+                rounds: RoundSchedule::Short { rounds: 3 },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let server_thread = thread::spawn(move || Server::new(config).start_server().unwrap());
+        thread::sleep(Duration::from_millis(100));
+
+        assert!(spectate("0.0.0.0", "7884", "snoop", None, Some("guess".to_string())).is_err());
+        let watch = |admin_token: Option<&str>| {
+            let admin_token = admin_token.map(str::to_string);
+            thread::spawn(move || spectate("0.0.0.0", "7884", "watcher", None, admin_token))
+        };
+        let public = watch(None);
+        let admin = watch(Some("secret"));
+
+        let mut client_threads = Vec::new();
+        for _ in 0..3 {
+            thread::sleep(Duration::from_millis(100));
+            client_threads.push(thread::spawn(|| {
+                RandomClient::new()
+                    .client("0.0.0.0", "7884", "random")
+                    .unwrap()
+            }));
+        }
+        // Joins once the game is under way and still sees it from the start
+        let late = watch(None);
+
+        server_thread.join().unwrap();
+        for client_thread in client_threads {
+            client_thread.join().unwrap();
+        }
+
+        let admin = admin.join().unwrap().unwrap();
+        assert_eq!(Turn::GameOver, GameEngine::replay(&admin).unwrap().turn());
+
+        let public = public.join().unwrap().unwrap();
+        let redacted: Vec<GameEvent> = admin.iter().map(|e| e.redacted()).collect();
+        assert_eq!(redacted, public);
+        assert_eq!(public, late.join().unwrap().unwrap());
+        assert!(GameEngine::replay(&public).is_err());
+    }
 }
@@ -0,0 +1,165 @@
+use crate::client::client::Client;
+use crate::game::engine::{GameEngine, Turn};
+use crate::network::action::{
+    AdjustBidRequest, BidRequest, ChooseTrumpRequest, PassCardRequest, PlayCardRequest,
+};
+use anyhow::{Context, Result};
+
+/// Bot deciding for a seat of a game played in this process
+pub type Bot = Box<dyn Client + Send>;
+
+/// Play `engine` to the end with `bots[i]` deciding for seat `i`
+///
+/// Like clients of a server, bots are told every event so far, redacted, before each decision.
+/// Nothing is sent over the network, so a game takes as long as the bots take to decide.
+///
+/// # Errors
+/// If a bot makes a move the rules do not allow
+pub fn play_local_game(engine: &mut GameEngine, bots: &mut [Bot]) -> Result<()> {
+    let mut events_seen = 0;
+    let mut tell_bots = |engine: &GameEngine, bots: &mut [Bot]| {
+        for event in engine.events()[events_seen..].iter() {
+            let event = event.redacted();
+            for bot in bots.iter_mut() {
+                bot.observe(&event);
+            }
+        }
+        events_seen = engine.events().len();
+    };
+
+    loop {
+        tell_bots(engine, bots);
+        let state = engine.game_state();
+        match engine.turn() {
+            Turn::ChooseTrump(i) => {
+                let suit = bots[i].choose_trump(&ChooseTrumpRequest {
+                    hand: engine.hand(i).to_vec(),
+                    state,
+                });
+                engine.choose_trump(i, suit)
+            }
+            Turn::Bid(i) => {
+                let bid = bots[i].bid(&BidRequest {
+                    hand: engine.hand(i).to_vec(),
+                    bids: engine.bids(),
+                    next_bidder: i,
+                    forbidden_bid: engine.forbidden_bid(),
+                    state,
+                });
+                engine.bid(i, bid)
+            }
+            Turn::BlindBid => {
+                let i = engine.pending_bidders()[0];
+                let bid = bots[i].bid(&BidRequest {
+                    hand: engine.hand(i).to_vec(),
+                    bids: Vec::new(),
+                    next_bidder: i,
+                    forbidden_bid: None,
+                    state,
+                });
+                engine.bid(i, bid)
+            }
+            Turn::PlayCard(i) => {
+                let card = bots[i].play_card(&PlayCardRequest {
+                    hand: engine.hand(i).to_vec(),
+                    playable_cards: engine.playable_cards(i),
+                    state,
+                });
+                engine.play_card(i, card).map(|_| ())
+            }
+            Turn::AdjustBid(i) => {
+                let adjustment = bots[i].adjust_bid(&AdjustBidRequest {
+                    hand: engine.hand(i).to_vec(),
+                    bid: engine.seats()[i].bid.unwrap_or(0),
+                    state,
+                });
+                engine.adjust_bid(i, adjustment)
+            }
+            Turn::PassCard(i) => {
+                let card = bots[i].pass_card(&PassCardRequest {
+                    hand: engine.hand(i).to_vec(),
+                    state,
+                });
+                engine.pass_card(i, card)
+            }
+            Turn::GameOver => break,
+        }
+        .with_context(|| format!("Bot broke the rules in round {}", engine.round()))?;
+    }
+    tell_bots(engine, bots);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::card::Card;
+    use crate::cards::suit::Suit;
+    use crate::client::random_client::RandomClient;
+    use crate::game::event::GameEvent;
+    use crate::game::rules::{GameRules, RoundSchedule};
+    use std::sync::{Arc, Mutex};
+
+    /// Random bot that remembers the events it was told
+    struct Recorder {
+        random: RandomClient,
+        events: Arc<Mutex<Vec<GameEvent>>>,
+    }
+
+    impl Client for Recorder {
+        fn new() -> Recorder {
+            Recorder {
+                random: RandomClient::with_seed(0),
+                events: Arc::default(),
+            }
+        }
+
+        fn bid(&mut self, request: &BidRequest) -> u8 {
+            self.random.bid(request)
+        }
+
+        fn choose_trump(&mut self, request: &ChooseTrumpRequest) -> Suit {
+            self.random.choose_trump(request)
+        }
+
+        fn play_card(&mut self, request: &PlayCardRequest) -> Card {
+            self.random.play_card(request)
+        }
+
+        fn adjust_bid(&mut self, request: &AdjustBidRequest) -> i8 {
+            self.random.adjust_bid(request)
+        }
+
+        fn pass_card(&mut self, request: &PassCardRequest) -> Card {
+            self.random.pass_card(request)
+        }
+
+        fn observe(&mut self, event: &GameEvent) {
+            self.events.lock().unwrap().push(event.clone());
+        }
+    }
+
+    #[test]
+    fn bots_are_told_every_redacted_event() {
+        let rules = GameRules {
+            blind_bidding: true,
+            rounds: RoundSchedule::Short { rounds: 3 },
+            ..Default::default()
+        };
+        let mut engine = GameEngine::with_rules(3, 4, rules).unwrap();
+        let recorder = Recorder::new();
+        let events = recorder.events.clone();
+        let mut bots: Vec<Bot> = vec![
+            Box::new(recorder),
+            Box::new(RandomClient::with_seed(1)),
+            Box::new(RandomClient::with_seed(2)),
+        ];
+
+        play_local_game(&mut engine, &mut bots).unwrap();
+
+        assert_eq!(Turn::GameOver, engine.turn());
+        let redacted: Vec<GameEvent> = engine.events().iter().map(|e| e.redacted()).collect();
+        assert_eq!(redacted, *events.lock().unwrap());
+    }
+}
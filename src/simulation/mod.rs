@@ -0,0 +1,3 @@
+pub mod local_game;
+pub mod report;
+pub mod simulator;
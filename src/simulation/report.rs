@@ -0,0 +1,313 @@
+use crate::game::event::GameEvent;
+use serde::Serialize;
+use std::fmt;
+
+/// z-score of a two sided 95% confidence interval
+const Z_95: f64 = 1.96;
+
+/// How one seat of a finished game did
+#[derive(Clone, Debug, PartialEq)]
+pub struct SeatResult {
+    pub score: i32,
+    /// 1 for an outright win. A shared win is split between the winners.
+    pub win: f64,
+    pub rounds: usize,
+    /// Rounds where the final bid matched the tricks taken
+    pub bids_made: usize,
+}
+
+/// Result of every seat of a finished game, indexed by seat
+pub fn seat_results(events: &[GameEvent]) -> Vec<SeatResult> {
+    let num_players = match events.first() {
+        Some(GameEvent::GameStarted { num_players, .. }) => *num_players,
+        _ => return Vec::new(),
+    };
+    let mut results = vec![
+        SeatResult {
+            score: 0,
+            win: 0.0,
+            rounds: 0,
+            bids_made: 0,
+        };
+        num_players
+    ];
+    let mut bids = vec![0i16; num_players];
+    let mut tricks = vec![0i16; num_players];
+
+    for event in events.iter() {
+        match event {
+            GameEvent::BidPlaced { player, bid } => bids[*player] = *bid as i16,
+            GameEvent::BidAdjusted { player, adjustment } => bids[*player] += *adjustment as i16,
+            GameEvent::TrickWon { player, .. } => tricks[*player] += 1,
+            GameEvent::RoundScored { scores, .. } => {
+                for (i, result) in results.iter_mut().enumerate() {
+                    result.score = scores[i];
+                    result.rounds += 1;
+                    result.bids_made += (bids[i] == tricks[i]) as usize;
+                }
+                tricks.fill(0);
+            }
+            _ => {}
+        }
+    }
+
+    let best = results.iter().map(|result| result.score).max();
+    let winners = results.iter().filter(|r| Some(r.score) == best).count();
+    for result in results.iter_mut() {
+        if Some(result.score) == best {
+            result.win = 1.0 / winners as f64;
+        }
+    }
+
+    results
+}
+
+/// How one bot of a simulation did over every game it played
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct BotStats {
+    /// Name the bot is reported under
+    pub name: String,
+    pub games: usize,
+    /// Games won. A shared win counts as a fraction.
+    pub wins: f64,
+    pub total_score: i64,
+    /// Sum of each game's score squared, for the spread of scores
+    pub total_squared_score: f64,
+    pub rounds: usize,
+    pub bids_made: usize,
+    /// Deals played from every seat in duplicate play
+    pub deals: usize,
+    /// Sum over deals of the bot's average score less the lineup's average score
+    pub total_advantage: f64,
+    pub total_squared_advantage: f64,
+}
+
+impl BotStats {
+    pub fn new(name: &str) -> BotStats {
+        BotStats {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn record(&mut self, result: &SeatResult) {
+        self.games += 1;
+        self.wins += result.win;
+        self.total_score += result.score as i64;
+        self.total_squared_score += (result.score as f64).powi(2);
+        self.rounds += result.rounds;
+        self.bids_made += result.bids_made;
+    }
+
+    /// Record how much better than the lineup's average the bot did over a duplicate deal
+    pub fn record_deal(&mut self, advantage: f64) {
+        self.deals += 1;
+        self.total_advantage += advantage;
+        self.total_squared_advantage += advantage.powi(2);
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        ratio(self.wins, self.games)
+    }
+
+    /// Half width of the 95% confidence interval of the win rate
+    pub fn win_rate_margin(&self) -> f64 {
+        let p = self.win_rate();
+        Z_95 * (p * (1.0 - p) / self.games.max(1) as f64).sqrt()
+    }
+
+    pub fn mean_score(&self) -> f64 {
+        ratio(self.total_score as f64, self.games)
+    }
+
+    /// Half width of the 95% confidence interval of the mean score
+    pub fn mean_score_margin(&self) -> f64 {
+        mean_margin(
+            self.total_score as f64,
+            self.total_squared_score,
+            self.games,
+        )
+    }
+
+    /// Average points per game the bot scored above the lineup's average on the same deals
+    pub fn mean_advantage(&self) -> f64 {
+        ratio(self.total_advantage, self.deals)
+    }
+
+    /// Half width of the 95% confidence interval of the mean advantage
+    pub fn advantage_margin(&self) -> f64 {
+        mean_margin(
+            self.total_advantage,
+            self.total_squared_advantage,
+            self.deals,
+        )
+    }
+
+    /// Share of rounds where the bot took exactly the tricks it bid
+    pub fn bid_accuracy(&self) -> f64 {
+        ratio(self.bids_made as f64, self.rounds)
+    }
+}
+
+/// Half width of the 95% confidence interval of the mean of `n` samples
+fn mean_margin(total: f64, total_squared: f64, n: usize) -> f64 {
+    if n < 2 {
+        return f64::INFINITY;
+    }
+    let n = n as f64;
+    let mean = total / n;
+    let variance = (total_squared - n * mean * mean) / (n - 1.0);
+    Z_95 * (variance.max(0.0) / n).sqrt()
+}
+
+fn ratio(part: f64, whole: usize) -> f64 {
+    match whole {
+        0 => 0.0,
+        whole => part / whole as f64,
+    }
+}
+
+/// Results of a simulation, one entry for each bot of the lineup
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct SimulationReport {
+    pub games: usize,
+    /// Deals played from every seat. Zero unless the games were played in duplicate.
+    pub deals: usize,
+    pub bots: Vec<BotStats>,
+}
+
+impl fmt::Display for SimulationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.deals {
+            0 => writeln!(f, "{} games, 95% confidence intervals", self.games)?,
+            deals => writeln!(
+                f,
+                "{} games of {} duplicate deals, 95% confidence intervals",
+                self.games, deals
+            )?,
+        }
+        write!(
+            f,
+            "{:<16} {:>18} {:>20} {:>13}",
+            "bot", "win rate", "mean score", "bid accuracy"
+        )?;
+        if self.deals > 0 {
+            write!(f, " {:>20}", "score vs field")?;
+        }
+        writeln!(f)?;
+
+        for bot in self.bots.iter() {
+            write!(
+                f,
+                "{:<16} {:>18} {:>20} {:>12.1}%",
+                bot.name,
+                format!(
+                    "{:.1}% ± {:.1}%",
+                    100.0 * bot.win_rate(),
+                    100.0 * bot.win_rate_margin()
+                ),
+                format!("{:.1} ± {:.1}", bot.mean_score(), bot.mean_score_margin()),
+                100.0 * bot.bid_accuracy()
+            )?;
+            if self.deals > 0 {
+                write!(
+                    f,
+                    " {:>20}",
+                    format!(
+                        "{:+.1} ± {:.1}",
+                        bot.mean_advantage(),
+                        bot.advantage_margin()
+                    )
+                )?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::rules::GameRules;
+
+    #[test]
+    fn results_from_events() {
+        let events = vec![
+            GameEvent::GameStarted {
+                num_players: 3,
+                seed: Some(0),
+                rules: GameRules::default(),
+            },
+            GameEvent::BidPlaced { player: 0, bid: 1 },
+            GameEvent::BidPlaced { player: 1, bid: 0 },
+            GameEvent::BidPlaced { player: 2, bid: 0 },
+            GameEvent::TrickWon {
+                player: 0,
+                cards: Vec::new(),
+            },
+            GameEvent::RoundScored {
+                round: 1,
+                scores: vec![30, 20, 20],
+            },
+            GameEvent::BidPlaced { player: 0, bid: 0 },
+            GameEvent::BidPlaced { player: 1, bid: 1 },
+            GameEvent::BidPlaced { player: 2, bid: 1 },
+            GameEvent::BidAdjusted {
+                player: 2,
+                adjustment: 1,
+            },
+            GameEvent::TrickWon {
+                player: 2,
+                cards: Vec::new(),
+            },
+            GameEvent::TrickWon {
+                player: 2,
+                cards: Vec::new(),
+            },
+            GameEvent::RoundScored {
+                round: 2,
+                scores: vec![50, 10, 60],
+            },
+        ];
+
+        let results = seat_results(&events);
+        assert_eq!(
+            vec![50, 10, 60],
+            results.iter().map(|r| r.score).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![2, 1, 2],
+            results.iter().map(|r| r.bids_made).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![0.0, 0.0, 1.0],
+            results.iter().map(|r| r.win).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn stats_and_confidence_intervals() {
+        let mut stats = BotStats::new("bot");
+        for (score, win) in [(10, 1.0), (30, 0.5), (20, 0.0), (40, 0.5)] {
+            stats.record(&SeatResult {
+                score,
+                win,
+                rounds: 2,
+                bids_made: 1,
+            });
+        }
+
+        assert_eq!(0.5, stats.win_rate());
+        assert_eq!(25.0, stats.mean_score());
+        assert_eq!(0.5, stats.bid_accuracy());
+        // Sample standard deviation of 10, 20, 30, 40 is 12.91
+        assert!((stats.mean_score_margin() - 1.96 * 12.9099 / 2.0).abs() < 0.01);
+        assert!((stats.win_rate_margin() - 0.49).abs() < 1e-9);
+
+        stats.record_deal(5.0);
+        stats.record_deal(-1.0);
+        assert_eq!(2.0, stats.mean_advantage());
+        assert!((stats.advantage_margin() - 1.96 * 3.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,258 @@
+use crate::client::client::Client;
+use crate::client::heuristic_client::HeuristicClient;
+use crate::client::ismcts_client::{IsmctsClient, SearchBudget};
+use crate::client::random_client::RandomClient;
+use crate::game::engine::GameEngine;
+use crate::game::rules::GameRules;
+use crate::simulation::local_game::{play_local_game, Bot};
+use crate::simulation::report::{seat_results, BotStats, SeatResult, SimulationReport};
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// Bot implementations that can play in a simulation
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BotKind {
+    Random,
+    Heuristic,
+    Ismcts,
+}
+
+impl BotKind {
+    /// Name the bot is reported under
+    pub fn name(&self) -> &'static str {
+        match self {
+            BotKind::Random => "random",
+            BotKind::Heuristic => "heuristic",
+            BotKind::Ismcts => "ismcts",
+        }
+    }
+
+    /// Create a bot whose decisions are reproducible from `seed`
+    pub fn create(&self, seed: u64, budget: SearchBudget) -> Bot {
+        match self {
+            BotKind::Random => Box::new(RandomClient::with_seed(seed)),
+            BotKind::Heuristic => Box::new(HeuristicClient::new()),
+            BotKind::Ismcts => {
+                let mut client = IsmctsClient::with_seed(seed);
+                client.set_budget(budget);
+                Box::new(client)
+            }
+        }
+    }
+}
+
+/// Many games between bots, played in this process on every core
+///
+/// Game `i` is dealt from `seed + i` and the lineup moves one seat to the left every game, so no
+/// bot keeps the advantage of a seat. The same settings always play the same games, however many
+/// threads play them, as long as the search bot is limited by iterations.
+///
+/// In duplicate play every deal is played once with the lineup in each seat, so every bot plays
+/// every hand from every position. Each bot's average score over a deal is then compared to the
+/// average of the whole lineup, which takes most of the card luck out of the comparison.
+#[derive(Clone, Debug)]
+pub struct Simulation {
+    /// Bot in each seat of the first game
+    pub lineup: Vec<BotKind>,
+    /// Games to play, or deals to play from every seat in duplicate play
+    pub games: usize,
+    pub duplicate: bool,
+    pub seed: u64,
+    pub rules: GameRules,
+    /// How long the search bot thinks before each decision
+    pub search_budget: SearchBudget,
+    /// Games played at the same time
+    pub threads: usize,
+}
+
+impl Simulation {
+    /// Simulation of `games` games with the standard rules for the table, on every core
+    pub fn new(lineup: Vec<BotKind>, games: usize) -> Simulation {
+        Simulation {
+            rules: GameRules::for_table(lineup.len()),
+            lineup,
+            games,
+            duplicate: false,
+            seed: 0,
+            search_budget: SearchBudget::default(),
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+        }
+    }
+
+    /// Play every game and report how each bot of the lineup did
+    ///
+    /// # Errors
+    /// If the rules can not be played by the lineup, or a bot breaks the rules
+    pub fn run(&self) -> Result<SimulationReport> {
+        // Fail before starting any threads
+        GameEngine::with_rules(self.lineup.len(), self.seed, self.rules.clone())?;
+
+        let next_game = AtomicUsize::new(0);
+        let mut results: Vec<(usize, Vec<Vec<SeatResult>>)> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..self.threads.clamp(1, self.games.max(1)))
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut results = Vec::new();
+                        loop {
+                            let game = next_game.fetch_add(1, Ordering::Relaxed);
+                            if game >= self.games {
+                                return Ok::<_, anyhow::Error>(results);
+                            }
+                            results.push((game, self.play_deal(game)?));
+                        }
+                    })
+                })
+                .collect();
+
+            let mut results = Vec::new();
+            for worker in workers {
+                match worker.join() {
+                    Ok(worker_results) => results.extend(worker_results?),
+                    Err(_) => bail!("Simulation thread panicked"),
+                }
+            }
+            Ok::<_, anyhow::Error>(results)
+        })?;
+        results.sort_by_key(|(game, _)| *game);
+
+        let mut bots: Vec<BotStats> = self
+            .lineup
+            .iter()
+            .map(|bot| BotStats::new(bot.name()))
+            .collect();
+        for (_, deal_results) in results.iter() {
+            for game_results in deal_results.iter() {
+                for (stats, result) in bots.iter_mut().zip(game_results) {
+                    stats.record(result);
+                }
+            }
+
+            if self.duplicate {
+                let average = |j: usize| {
+                    let total: i64 = deal_results.iter().map(|game| game[j].score as i64).sum();
+                    total as f64 / deal_results.len() as f64
+                };
+                let averages: Vec<f64> = (0..self.lineup.len()).map(average).collect();
+                let field = averages.iter().sum::<f64>() / averages.len() as f64;
+                for (stats, average) in bots.iter_mut().zip(averages) {
+                    stats.record_deal(average - field);
+                }
+            }
+        }
+
+        Ok(SimulationReport {
+            games: results.iter().map(|(_, deal)| deal.len()).sum(),
+            deals: if self.duplicate { self.games } else { 0 },
+            bots,
+        })
+    }
+
+    /// Play the games of deal number `deal`: every rotation of the lineup in duplicate play,
+    /// otherwise a single game
+    fn play_deal(&self, deal: usize) -> Result<Vec<Vec<SeatResult>>> {
+        let num_players = self.lineup.len();
+        match self.duplicate {
+            true => (0..num_players)
+                .map(|rotation| self.play(deal, rotation))
+                .collect(),
+            false => Ok(vec![self.play(deal, deal % num_players)?]),
+        }
+    }
+
+    /// Play deal number `deal` with the lineup moved `rotation` seats to the left. Results are
+    /// in lineup order.
+    fn play(&self, deal: usize, rotation: usize) -> Result<Vec<SeatResult>> {
+        let num_players = self.lineup.len();
+        let seed = self.seed.wrapping_add(deal as u64);
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+
+        // Lineup entry `j` sits in seat `(j + rotation) % num_players`
+        let seat = |j: usize| (j + rotation) % num_players;
+        let mut bots: Vec<Option<Bot>> = (0..num_players).map(|_| None).collect();
+        for (j, kind) in self.lineup.iter().enumerate() {
+            bots[seat(j)] = Some(kind.create(rng.gen(), self.search_budget));
+        }
+        let mut bots: Vec<Bot> = bots.into_iter().flatten().collect();
+
+        let mut engine = GameEngine::with_rules(num_players, seed, self.rules.clone())?;
+        play_local_game(&mut engine, &mut bots).with_context(|| format!("Game {} failed", deal))?;
+
+        let results = seat_results(engine.events());
+        Ok((0..num_players).map(|j| results[seat(j)].clone()).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::rules::RoundSchedule;
+
+    fn simulation(threads: usize) -> Simulation {
+        let mut simulation = Simulation::new(
+            vec![BotKind::Heuristic, BotKind::Random, BotKind::Random],
+            60,
+        );
+        simulation.rules.rounds = RoundSchedule::Short { rounds: 6 };
+        simulation.seed = 11;
+        simulation.threads = threads;
+        simulation
+    }
+
+    #[test]
+    fn same_games_on_any_number_of_threads() {
+        let report = simulation(3).run().unwrap();
+        assert_eq!(simulation(1).run().unwrap(), report);
+
+        assert_eq!(60, report.games);
+        let wins: f64 = report.bots.iter().map(|bot| bot.wins).sum();
+        assert!((wins - 60.0).abs() < 1e-9);
+        for bot in report.bots.iter() {
+            assert_eq!(60, bot.games);
+            assert_eq!(60 * 6, bot.rounds);
+        }
+    }
+
+    #[test]
+    fn heuristic_bot_beats_random_bots() {
+        let report = simulation(2).run().unwrap();
+        let (heuristic, random) = (&report.bots[0], &report.bots[1]);
+
+        assert!(heuristic.mean_score() > random.mean_score());
+        assert!(heuristic.bid_accuracy() > random.bid_accuracy());
+    }
+
+    #[test]
+    fn duplicate_play_compares_bots_on_the_same_deals() {
+        let mut simulation = simulation(2);
+        simulation.games = 8;
+        simulation.duplicate = true;
+        let report = simulation.run().unwrap();
+
+        assert_eq!(8 * 3, report.games);
+        assert_eq!(8, report.deals);
+        let (heuristic, random) = (&report.bots[0], &report.bots[1]);
+        assert_eq!(24, heuristic.games);
+        assert_eq!(8, heuristic.deals);
+        assert!(heuristic.mean_advantage() - heuristic.advantage_margin() > 0.0);
+        assert!(random.mean_advantage() < 0.0);
+        let advantages: f64 = report.bots.iter().map(|bot| bot.mean_advantage()).sum();
+        assert!(advantages.abs() < 1e-9);
+
+        // The heuristic bot always makes the same decisions, so its copies do equally well
+        simulation.lineup = vec![BotKind::Heuristic; 3];
+        for bot in simulation.run().unwrap().bots.iter() {
+            assert_eq!(0.0, bot.mean_advantage());
+        }
+    }
+
+    #[test]
+    fn lineup_must_fit_the_rules() {
+        assert!(Simulation::new(vec![BotKind::Random], 1).run().is_err());
+    }
+}